@@ -0,0 +1,64 @@
+extern crate draw_with_circles;
+
+use draw_with_circles::dwc::*;
+
+const USAGE: &str = "\
+Convert paths between the text and binary (.dwcp) formats
+
+usage: convert [--f64] <input> <output>
+
+the direction comes from the extension of the output: .dwcp writes binary, anything else text
+  --f64    store binary coordinates as f64 instead of f32";
+
+fn convert(input: &str, output: &str, double: bool) -> Result<usize, Error>
+{
+    let strokes: Vec<Vec<Complex>> = if has_extension(input, "dwcp")
+    {
+        load_from_binary(input)?
+    }
+    else
+    {
+        let contents: String = std::fs::read_to_string(input).map_err(|e| Error::io(input, e))?;
+        parse_text_path(&contents, input)?.strokes
+    };
+
+    if has_extension(output, "dwcp")
+    {
+        save_to_binary(output, &strokes, double)?;
+    }
+    else
+    {
+        save_strokes_to_txt(output, &strokes)?;
+    }
+
+    return Ok(strokes.iter().map(|s| s.len()).sum());
+}
+
+pub fn main()
+{
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let double: bool = args.iter().any(|a| a == "--f64");
+    let files: Vec<&String> = args.iter().filter(|a| *a != "--f64").collect();
+
+    if args.iter().any(|a| a == "-h" || a == "--help")
+    {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if files.len() != 2 || files.iter().any(|f| f.starts_with('-'))
+    {
+        eprintln!("error: expected an input and an output file\n\n{}", USAGE);
+        std::process::exit(2);
+    }
+
+    match convert(files[0], files[1], double)
+    {
+        Ok(count) => println!("{} points written to {}", count, files[1]),
+        Err(e) =>
+        {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        },
+    }
+}
\ No newline at end of file
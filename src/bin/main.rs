@@ -5,31 +5,65 @@ use draw_with_circles::dwc::*;
 
 pub fn main()
 {
-    let dir: String = String::from("./paths/");
-    let mut file: String = String::from("default.txt");
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    println!("use default paths? (yes or no)");
-    let mut option: String = String::new();
-    std::io::stdin().read_line(&mut option).expect("??????");
-    option.pop(); option.pop(); 
+    let settings: Settings = match load_settings(&args)
+    {
+        Ok(Command::Run(settings)) => settings,
+        Ok(Command::Help) =>
+        {
+            println!("{}", HELP);
+            return;
+        },
+        Err(Error::Usage(e)) =>
+        {
+            eprintln!("error: {}\nrun with --help to see the options", e);
+            std::process::exit(2);
+        },
+        Err(e) =>
+        {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        },
+    };
 
-    assert!(option.eq("yes") || option.eq("no"));
+    if !std::path::Path::new(&settings.file).is_file()
+    {
+        eprintln!("error: {}: no such file", settings.file);
+        std::process::exit(1);
+    }
 
-    if option.eq("no")
+    if let Err(e) = run(settings, args)
     {
-        file.clear();
-        println!("enter your file name (in paths and must be .txt format)");
-        std::io::stdin().read_line(&mut file).expect("invalid file");
-        file.pop(); file.pop();
-        assert!(file.contains(".txt"));
+        eprintln!("error: {}", e);
+        std::process::exit(1);
     }
+}
 
-    let path: String = dir + &file;
+fn run(settings: Settings, args: Vec<String>) -> Result<(), Error>
+{
+    if settings.output == OutputMode::Coefficients
+    {
+        // terms come in +f/-f pairs
+        let path: Vec<Complex> = load_shape_traced(&settings.file, settings.image_trace)?;
+        let mut epicycles: Vec<Epicycle> = compute_epicycles(&path, settings.term_count / 2 + 2)?;
+        epicycles.truncate(settings.term_count);
 
-    println!("{}", path);
+        print!("{}", format_coefficients(&epicycles));
+        return Ok(());
+    }
 
-    let mut app: App = App::new("Draw with circles", 1000, 800);
-    app.init_renderer(0.8, 1.0);
-    app.init(path, 1.0 / 900.0, 880, 60);
+    let mut app: App = App::new("Draw with circles", settings.window_width, settings.window_height, settings.vsync)?;
+    app.init_renderer(1.0, 1.0);
+    app.set_command_line(args);
+
+    if settings.output == OutputMode::Fullscreen
+    {
+        app.toggle_fullscreen();
+    }
+
+    app.apply_settings(&settings)?;
     app.run();
-}
+
+    return Ok(());
+}
\ No newline at end of file
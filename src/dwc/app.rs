@@ -3,14 +3,46 @@ extern crate sdl2;
 use crate::dwc::epicycle::*;
 use crate::dwc::draw::*;
 use crate::dwc::file_loader::*;
+use crate::dwc::contour::*;
+use crate::dwc::trail_style::*;
+use crate::dwc::complex::*;
+use crate::dwc::trail::*;
+use crate::dwc::path::*;
+use crate::dwc::common::*;
+use crate::dwc::playback::*;
+use crate::dwc::convergence::*;
+use crate::dwc::error::*;
+use crate::dwc::settings::*;
+use crate::dwc::cli::*;
+use crate::dwc::config::*;
+use crate::dwc::theme::*;
+use crate::dwc::watch::*;
+use crate::dwc::browser::*;
+use crate::dwc::screenshot::*;
+
+use std::time::Duration;
+use std::time::Instant;
 
 use sdl2::EventPump;
 use sdl2::VideoSubsystem;
 use sdl2::Sdl;
 use sdl2::video::Window;
 use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
 use sdl2::event::Event;
+use sdl2::event::WindowEvent;
 use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+
+const ERROR_SAMPLE_COUNT: usize = 256;
+const MAX_FRAME_TIME: f32 = 0.25; // longer frames (window dragged, debugger) are not caught up on
+const PATHS_DIR: &str = "./paths/"; // sketches are saved here, and the browser lists it
+const MAX_FREQUENCY: usize = 300; // coefficients are computed once for every frequency below this
+const FIT_MARGIN: f32 = 0.05; // used when the settings give no scale
+const WATCH_INTERVAL: Duration = Duration::from_millis(500); // how often the loaded file is checked for changes
+const MESSAGE_DURATION: f32 = 5.0; // seconds a message stays on screen
+const BROWSER_ROWS: usize = 9;
+const SCREENSHOT_DIR: &str = "./screenshots/";
 
 #[allow(dead_code)]
 pub struct App
@@ -20,57 +52,269 @@ pub struct App
     video_subsystem: VideoSubsystem,
     renderer: Renderer,
 
-    t: f32,
-    dt: f32,
+    playback: Playback,
     shape: Trail,
-    epicycles: Vec<Epicycle>,
-    begin_draw: bool,
-    max_framerate: u8
+    epicycles: Vec<Epicycle>, // the terms currently shown, changed through set_epicycles
+    coefficients: Vec<Epicycle>, // every computed term, in term_order
+    term_count: usize,
+    term_order: TermOrder,
+    convergence: Option<Convergence>, // sweep the term count to show the series converging
+    hud: bool,
+    file: String, // the loaded file, empty for a sketch
+    settings_file: String, // the file of the last settings applied, which may not be the one shown
+    file_name: String, // shown in the hud
+    watch: Option<FileWatch>, // reloads the file when it is saved again
+    message: Option<(String, Instant)>, // shown at the bottom of the window for a while
+    browser: Option<Browser>, // the list of shapes to pick from, while shown
+    screenshot: bool, // save the next frame
+    args: Vec<String>, // command line the settings are reloaded with
+    fps: f32, // smoothed frame rate
+    vsync: bool,
+    max_framerate: u32, // frames per second when there is no vsync, 0 for no limit
+    image_trace: ImageTrace,
+    path: Vec<Complex>, // the loaded shape, kept to compare the drawing against
+    centered_path: Vec<Complex>, // the shape closed and centred like the series, under the convergence demo
+    curve: Option<Vec<Complex>>, // the closed curve of the current terms, None until it is needed again
+    error: Option<f32>, // reconstruction error of the current terms, shown in the hud
+    trail_style: TrailStyle,
+    theme: Theme, // a copy of THEMES[theme_index] with the colours given in the settings
+    theme_index: usize,
+    circle_coloring: CircleColoring,
+    persist: bool, // keep the whole drawing on screen once a period has been traced
+    persist_start: f32,
+    traced: Vec<Complex>, // points traced since persist_start
+    drag_from: Option<(i32, i32)>, // last mouse position while panning
+    scrubbing: bool, // the timeline is being dragged
+    auto_fit: Option<f32>, // frame the shape automatically, with this margin
+    drawing: bool, // the left mouse button sketches a new shape
+    strokes: Vec<Vec<Complex>>, // finished strokes of the sketch
+    stroke: Option<Vec<Complex>> // stroke being drawn
 }
 
 impl App
 {
-    pub fn new(title: &str, window_width: u32, window_height: u32) -> App
+    pub fn new(title: &str, window_width: u32, window_height: u32, vsync: bool) -> Result<App, Error>
     {
-        let sdl_context: Sdl = sdl2::init().unwrap();
-        let video_subsystem: VideoSubsystem = sdl_context.video().unwrap();
+        let sdl_context: Sdl = sdl2::init().map_err(Error::Sdl)?;
+        let video_subsystem: VideoSubsystem = sdl_context.video().map_err(Error::Sdl)?;
 
         let window: Window = video_subsystem.window(&title, window_width, window_height)
                             .position_centered()
+                            .resizable()
+                            .allow_highdpi()
                             .build()
-                            .unwrap();
+                            .map_err(|e| Error::Sdl(e.to_string()))?;
 
-        let renderer: Renderer = Renderer::new(window, 0.0, 0.0);
+        let renderer: Renderer = Renderer::new(window, 0.0, 0.0, vsync)?;
 
-        let event_pump = sdl_context.event_pump().unwrap();
+        let event_pump = sdl_context.event_pump().map_err(Error::Sdl)?;
 
-        return App 
+        return Ok(App 
         {
             event: event_pump,
             context: sdl_context,
             video_subsystem: video_subsystem,
             renderer: renderer,
+            vsync: vsync,
             max_framerate: 0,
-            t: 0.0,
-            dt: 0.0,
+            playback: Playback::new(0.0),
             shape: Trail::new(0),
             epicycles: Vec::new(),
-            begin_draw: false
-        };
+            coefficients: Vec::new(),
+            term_count: 298,
+            term_order: TermOrder::Frequency,
+            convergence: None,
+            hud: true,
+            file: String::new(),
+            settings_file: String::new(),
+            file_name: String::new(),
+            watch: None,
+            message: None,
+            browser: None,
+            screenshot: false,
+            args: Vec::new(),
+            fps: 0.0,
+            image_trace: ImageTrace::Contours(ContourSelection::Largest),
+            path: Vec::new(),
+            centered_path: Vec::new(),
+            curve: None,
+            error: None,
+            trail_style: TrailStyle::new(TrailColoring::Solid, false),
+            theme: THEMES[0].clone(),
+            theme_index: 0,
+            circle_coloring: CircleColoring::Uniform,
+            persist: false,
+            persist_start: 0.0,
+            traced: Vec::new(),
+            drag_from: None,
+            scrubbing: false,
+            auto_fit: None,
+            drawing: false,
+            strokes: Vec::new(),
+            stroke: None
+        });
     }
 }
 
 impl App {
 
-    pub fn init(&mut self, file: String, delta_time: f32, trail_length: usize, max_framerate: u8)
+    // speed is in periods per second, the trail length in ticks of the simulation
+    pub fn init(&mut self, file: String, speed: f32, trail_length: usize, max_framerate: u32) -> Result<(), Error>
     {
-        self.dt = delta_time;
+        self.playback = Playback::new(speed);
         self.shape = Trail::new(trail_length);
         self.max_framerate = max_framerate;
 
-        let shape = load_from_txt(&file);
+        return self.open_file(&file);
+    }
+
+    // settings from the config file and the command line, can be applied again while running
+    // vsync and the output mode are only used at startup
+    pub fn apply_settings(&mut self, settings: &Settings) -> Result<(), Error>
+    {
+        // a file browsed, dropped or sketched since stays until the file setting itself changes
+        let file: Option<String> = if settings.file != self.settings_file
+        {
+            Some(settings.file.clone())
+        }
+        else if settings.image_trace != self.image_trace && !self.file.is_empty()
+        {
+            Some(self.file.clone())
+        }
+        else
+        {
+            None
+        };
+
+        // load first so that a broken file leaves everything as it was
+        let path: Option<(String, Vec<Complex>)> = match file
+        {
+            Some(file) => Some((file.clone(), load_shape_traced(&file, settings.image_trace)?)),
+            None => None,
+        };
+
+        self.renderer.set_window_size(settings.window_width, settings.window_height).map_err(Error::Sdl)?;
+
+        match settings.scale
+        {
+            Some(scale) =>
+            {
+                self.renderer.scale = scale;
+                self.auto_fit = None;
+            },
+            None => self.auto_fit = Some(FIT_MARGIN),
+        }
+
+        self.set_theme(settings.theme);
+        self.theme.trail = settings.trail_color.unwrap_or(self.theme.trail);
+        self.theme.circle = settings.circle_color.map(|c| Color::RGBA(c.r, c.g, c.b, self.theme.circle.a)).unwrap_or(self.theme.circle);
+        self.theme.background = settings.background_color.unwrap_or(self.theme.background);
+        self.circle_coloring = settings.circles;
+        self.trail_style = settings.trail_style;
+        self.init_strokes(settings.antialiasing, settings.trail_width, settings.arm_width, settings.circle_width);
+        self.image_trace = settings.image_trace;
+        self.max_framerate = settings.max_framerate;
+        self.playback.rate = settings.rate;
+        self.term_count = settings.term_count.max(1);
+
+        if settings.trail_length != self.shape.get_max_length()
+        {
+            self.shape = Trail::new(settings.trail_length);
+        }
+
+        match path
+        {
+            Some((file, path)) =>
+            {
+                self.set_file(&file, path)?;
+                self.settings_file = settings.file.clone();
+            },
+            None =>
+            {
+                self.apply_term_count();
+                self.set_persist(self.persist);
+                self.rebuild_trail();
+            },
+        }
+
+        if self.convergence.is_some()
+        {
+            self.convergence = Some(Convergence::new(self.term_count.min(self.coefficients.len())));
+        }
+
+        self.fit_view();
+
+        return Ok(());
+    }
+
+    // the arguments (without the program name) read again with the config file when F5 is pressed
+    pub fn set_command_line(&mut self, args: Vec<String>)
+    {
+        self.args = args;
+    }
+
+    // choose how bitmaps are traced, must be called before init
+    pub fn set_image_trace(&mut self, trace: ImageTrace)
+    {
+        self.image_trace = trace;
+    }
 
-        self.epicycles = compute_epicycles(&shape, 150);
+    // how many terms of the series are drawn, can be changed while running
+    pub fn set_term_count(&mut self, term_count: usize)
+    {
+        self.term_count = term_count.max(1);
+        self.apply_term_count();
+    }
+
+    // one of THEMES, replacing the colours set so far
+    pub fn set_theme(&mut self, index: usize)
+    {
+        self.theme_index = index % THEMES.len();
+        self.theme = THEMES[self.theme_index].clone();
+        self.renderer.set_blend_mode(if self.theme.additive { BlendMode::Add } else { BlendMode::Blend });
+
+        // the persisted drawing has the colour of the old theme
+        self.set_persist(self.persist);
+    }
+
+    pub fn set_circle_coloring(&mut self, coloring: CircleColoring)
+    {
+        self.circle_coloring = coloring;
+    }
+
+    pub fn set_colors(&mut self, trail_color: Color, background_color: Color)
+    {
+        self.theme.trail = trail_color;
+        self.theme.background = background_color;
+    }
+
+    pub fn toggle_fullscreen(&mut self)
+    {
+        if let Err(e) = self.renderer.toggle_fullscreen()
+        {
+            self.notify(format!("fullscreen: {}", e));
+        }
+
+        self.fit_view();
+    }
+
+    pub fn set_trail_style(&mut self, style: TrailStyle)
+    {
+        self.trail_style = style;
+    }
+
+    // margin is a fraction of the window left free around the shape, None keeps the scale given to init_renderer
+    pub fn set_auto_fit(&mut self, margin: Option<f32>)
+    {
+        self.auto_fit = margin;
+    }
+
+    pub fn set_persist(&mut self, persist: bool)
+    {
+        self.persist = persist;
+        self.persist_start = self.playback.t;
+        self.traced.clear();
+        self.renderer.clear_persisted();
     }
 
     pub fn init_renderer(&mut self, draw_scale: f32, pixel_size: f32)
@@ -79,13 +323,162 @@ impl App {
         self.renderer.set_pixel_size(pixel_size);
     }
 
+    // stroke widths are in pixels, lines wider than one pixel are always antialiased
+    pub fn init_strokes(&mut self, antialiasing: bool, trail_width: f32, arm_width: f32, circle_width: f32)
+    {
+        self.renderer.antialiasing = antialiasing;
+        self.renderer.trail_width = trail_width;
+        self.renderer.arm_width = arm_width;
+        self.renderer.circle_width = circle_width;
+    }
+
     pub fn run(&mut self)
     {
+        let mut previous: Instant = Instant::now();
+        let mut accumulator: f32 = 0.0;
+
         'running: loop {
-            for event in self.event.poll_iter() {
+            let frame_start: Instant = Instant::now();
+
+            let events: Vec<Event> = self.event.poll_iter().collect();
+
+            for event in events {
                 match event {
                     Event::KeyDown { keycode: Some(Keycode::Space), .. } => {
-                        self.begin_draw = true;
+                        self.playback.toggle_pause();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Up), .. } => {
+                        self.playback.faster();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Down), .. } => {
+                        self.playback.slower();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Equals | Keycode::KpPlus), .. } => {
+                        self.change_term_count(1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Minus | Keycode::KpMinus), .. } => {
+                        self.change_term_count(-1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::O), .. } => {
+                        self.term_order = if self.term_order == TermOrder::Frequency { TermOrder::Magnitude } else { TermOrder::Frequency };
+                        sort_epicycles(&mut self.coefficients, self.term_order);
+                        self.apply_term_count();
+                        self.set_persist(self.persist);
+                        self.rebuild_trail();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::C), .. } => {
+                        self.toggle_convergence();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::T), .. } => {
+                        self.set_theme(self.theme_index + 1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::M), .. } => {
+                        self.circle_coloring = match self.circle_coloring
+                        {
+                            CircleColoring::Uniform => CircleColoring::Magnitude,
+                            CircleColoring::Magnitude => CircleColoring::Frequency,
+                            CircleColoring::Frequency => CircleColoring::Uniform,
+                        };
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::H), .. } => {
+                        self.hud = !self.hud;
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::R), .. } => {
+                        self.playback.reverse();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Left), .. } => {
+                        self.step_frames(-1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Right), .. } => {
+                        self.step_frames(1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::P), .. } => {
+                        self.set_persist(!self.persist);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::F), .. } => {
+                        self.renderer.camera.follow = !self.renderer.camera.follow;
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Home), .. } => {
+                        self.renderer.camera.reset();
+                        self.fit_view();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::D), .. } => {
+                        self.drawing = !self.drawing;
+                        self.stroke = None;
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Backspace), .. } if self.drawing => {
+                        self.strokes.pop();
+                        self.rebuild_drawing();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Delete), .. } if self.drawing => {
+                        self.strokes.clear();
+                        self.rebuild_drawing();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::S), .. } if self.drawing => {
+                        self.save_drawing();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::F11), .. } => {
+                        self.toggle_fullscreen();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::B), .. } => {
+                        self.toggle_browser();
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::PageUp), .. } => {
+                        self.browse(-1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::PageDown), .. } => {
+                        self.browse(1);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::Return | Keycode::KpEnter), .. } if self.browser.is_some() => {
+                        self.open_selected();
+                    },
+                    Event::DropFile { filename, .. } => {
+                        self.open_dropped(&filename);
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::F12), .. } => {
+                        self.screenshot = true;
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::F5), .. } => {
+                        self.reload_settings();
+                    },
+                    // moving the window to another screen can change its pixel density
+                    Event::Window { win_event: WindowEvent::SizeChanged(..) | WindowEvent::Resized(..) | WindowEvent::Moved(..), .. } => {
+                        let changed: bool = self.renderer.update_size();
+
+                        if changed
+                        {
+                            self.fit_view();
+                        }
+                    },
+                    Event::MouseWheel { y, .. } => {
+                        self.zoom_at_cursor(1.1_f32.powi(y));
+                    },
+                    Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } if self.on_timeline(x, y) => {
+                        self.scrubbing = true;
+                        self.scrub_to(x);
+                    },
+                    Event::MouseButtonUp { mouse_btn: MouseButton::Left, .. } if self.scrubbing => {
+                        self.scrubbing = false;
+                    },
+                    Event::MouseMotion { x, .. } if self.scrubbing => {
+                        self.scrub_to(x);
+                    },
+                    Event::MouseButtonDown { mouse_btn: MouseButton::Left, .. } if self.drawing => {
+                        self.stroke = Some(Vec::new());
+                    },
+                    Event::MouseButtonUp { mouse_btn: MouseButton::Left, .. } if self.stroke.is_some() => {
+                        self.strokes.push(self.stroke.take().unwrap());
+                        self.rebuild_drawing();
+                    },
+                    Event::MouseButtonDown { mouse_btn: MouseButton::Left | MouseButton::Right, x, y, .. } => {
+                        self.drag_from = Some((x, y));
+                    },
+                    Event::MouseButtonUp { mouse_btn: MouseButton::Left | MouseButton::Right, .. } => {
+                        self.drag_from = None;
+                    },
+                    Event::MouseMotion { x, y, .. } => {
+                        self.drag_to(x, y);
+                        self.extend_stroke(x, y);
                     },
                     Event::Quit {..} |
                     Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
@@ -95,35 +488,588 @@ impl App {
                 }
             }
             
-            self.update();
+            self.reload_if_modified();
+
+            if let Some(browser) = &mut self.browser
+            {
+                browser.load_thumbnail(BROWSER_ROWS, self.image_trace);
+            }
+
+            // the simulation moves in fixed ticks, however long the frames take
+            let frame_time: f32 = previous.elapsed().as_secs_f32().min(MAX_FRAME_TIME);
+            previous = Instant::now();
+            accumulator += frame_time;
+
+            while accumulator >= TICK
+            {
+                self.update();
+                accumulator -= TICK;
+            }
+
+            if frame_time > 0.0
+            {
+                self.fps = if self.fps > 0.0 { self.fps * 0.95 + 0.05 / frame_time } else { 1.0 / frame_time };
+            }
 
             self.render();
+
+            if !self.vsync && self.max_framerate > 0
+            {
+                let frame: f32 = 1.0 / self.max_framerate as f32;
+                let spent: f32 = frame_start.elapsed().as_secs_f32();
+
+                if spent < frame
+                {
+                    ::std::thread::sleep(core::time::Duration::from_secs_f32(frame - spent));
+                }
+            }
         }
     }
 }
 
 impl App
 {
+    // frame both the drawing and the circles of the chain
+    fn fit_view(&mut self)
+    {
+        let margin: f32 = match self.auto_fit { Some(margin) => margin, None => return };
+
+        // compute_epicycles leaves out the constant term, so the drawing is centred on the origin
+        let center: Complex = centroid(&self.path);
+        let centered: Vec<Complex> = self.path.iter().map(|p| p - &center).collect();
+
+        let reach: f32 = self.epicycles.iter().map(|e| e.c0.magnitude()).sum();
+        let chain: Bounds = Bounds::new(Complex::new(-reach, -reach), Complex::new(reach, reach));
+
+        let bounds: Bounds = match Bounds::from_points(&centered) { Some(bounds) => bounds.union(&chain), None => chain };
+
+        self.renderer.fit_view(&bounds, margin);
+    }
+
+    fn set_file(&mut self, file: &str, path: Vec<Complex>) -> Result<(), Error>
+    {
+        self.set_path(path, file)?;
+        self.file = String::from(file);
+        self.file_name = std::path::Path::new(file).file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_else(|| String::from(file));
+        self.watch = Some(FileWatch::new(file, WATCH_INTERVAL));
+
+        return Ok(());
+    }
+
+    // start over with another file, from the first frame and with the camera reset
+    fn open_file(&mut self, file: &str) -> Result<(), Error>
+    {
+        let path: Vec<Complex> = load_shape_traced(file, self.image_trace)?;
+        self.set_file(file, path)?;
+
+        if self.convergence.is_some()
+        {
+            self.convergence = Some(Convergence::new(self.term_count.min(self.coefficients.len())));
+        }
+
+        self.renderer.camera.reset();
+        self.fit_view();
+
+        return Ok(());
+    }
+
+    fn toggle_browser(&mut self)
+    {
+        if self.browser.take().is_some()
+        {
+            return;
+        }
+
+        match Browser::new(PATHS_DIR)
+        {
+            Ok(mut browser) =>
+            {
+                browser.select_file(&self.file);
+                self.browser = Some(browser);
+            },
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    // move the selection of the browser, showing it first if needed
+    fn browse(&mut self, offset: i32)
+    {
+        if self.browser.is_none()
+        {
+            self.toggle_browser();
+            return;
+        }
+
+        if let Some(browser) = &mut self.browser
+        {
+            browser.select_next(offset);
+        }
+    }
+
+    fn open_selected(&mut self)
+    {
+        let file: String = match self.browser.as_ref().and_then(|b| b.selected_file()) { Some(file) => file.clone(), None => return };
+
+        match self.open_file(&file)
+        {
+            Ok(()) => self.browser = None,
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    // any file load_shape understands, whatever its extension
+    fn open_dropped(&mut self, file: &str)
+    {
+        match self.open_file(file)
+        {
+            Ok(()) =>
+            {
+                self.browser = None;
+                self.playback.paused = false;
+            },
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    // the loaded file was saved again, swap in the new shape where the animation is
+    // a broken file keeps the old shape on screen
+    fn reload_if_modified(&mut self)
+    {
+        let changed: bool = match &mut self.watch { Some(watch) => watch.poll(), None => false };
+
+        if !changed
+        {
+            return;
+        }
+
+        let t: f32 = self.playback.t;
+        let result: Result<(), Error> = match load_shape_traced(&self.file, self.image_trace)
+        {
+            Ok(path) => self.set_path(path, &self.file.clone()),
+            Err(e) => Err(e),
+        };
+
+        match result
+        {
+            Ok(()) =>
+            {
+                self.playback.t = t;
+                self.rebuild_trail();
+                self.fit_view();
+                self.notify(format!("reloaded {}", self.file_name));
+            },
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    // shown in the window and on stderr, which keeps stdout for the coefficients
+    fn notify(&mut self, text: String)
+    {
+        eprintln!("{}", text);
+        self.message = Some((text, Instant::now()));
+    }
+
+    // read the config file and the command line again, a mistake in either keeps the current settings
+    fn reload_settings(&mut self)
+    {
+        let result: Result<(), Error> = match load_settings(&self.args)
+        {
+            Ok(Command::Run(settings)) => self.apply_settings(&settings),
+            Ok(Command::Help) => Ok(()),
+            Err(e) => Err(e),
+        };
+
+        match result
+        {
+            Ok(()) => self.notify(String::from("settings reloaded")),
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    // replace the animated shape, `name` is what errors call it
+    // an empty path clears the drawing
+    fn set_path(&mut self, path: Vec<Complex>, name: &str) -> Result<(), Error>
+    {
+        self.coefficients = if path.is_empty() { Vec::new() } else { compute_epicycles(&path, MAX_FREQUENCY).map_err(|e| e.with_source(name))? };
+        let center: Complex = centroid(&path);
+        self.centered_path = path.iter().map(|p| p - &center).collect();
+        self.centered_path.extend(self.centered_path.first().cloned());
+        self.path = path;
+        self.curve = None;
+        self.error = None;
+        sort_epicycles(&mut self.coefficients, self.term_order);
+        self.apply_term_count();
+        self.playback.t = 0.0;
+        self.shape.clear();
+        self.set_persist(self.persist);
+
+        return Ok(());
+    }
+
+    fn extend_stroke(&mut self, x: i32, y: i32)
+    {
+        let (x, y) = self.renderer.window_to_canvas(x, y);
+        let point: Complex = self.renderer.to_world(x, y);
+
+        if let Some(stroke) = &mut self.stroke
+        {
+            stroke.push(point);
+        }
+    }
+
+    // animate the sketch, every stroke is closed into one path
+    fn rebuild_drawing(&mut self)
+    {
+        let strokes: Vec<Vec<Complex>> = self.strokes.iter().filter(|s| s.len() > 1).cloned().collect();
+
+        if strokes.is_empty()
+        {
+            // cannot fail, there is nothing to compute
+            let _ = self.set_path(Vec::new(), "drawing");
+            return;
+        }
+
+        // the animation is centred on the mean of the path, move the strokes and the camera along so nothing jumps on screen
+        let center: Complex = centroid(&join_strokes(&strokes));
+
+        for p in self.strokes.iter_mut().flatten()
+        {
+            *p -= &center;
+        }
+
+        self.renderer.camera.center -= &center;
+
+        let joined: Vec<Complex> = join_strokes(&strokes).iter().map(|p| p - &center).collect();
+
+        // a stroke that never moved has nothing to draw
+        if let Err(e) = self.set_path(resample(&joined, SHAPE_SAMPLE_COUNT), "drawing")
+        {
+            self.notify(e.to_string());
+            return;
+        }
+
+        self.file.clear();
+        self.file_name = String::from("drawing");
+        self.watch = None;
+        self.playback.paused = false;
+    }
+
+    // save the sketch next to the other paths, under the first free name
+    fn save_drawing(&mut self)
+    {
+        if self.path.is_empty()
+        {
+            return;
+        }
+
+        let mut index: usize = 1;
+
+        while std::path::Path::new(&format!("{}drawing_{}.txt", PATHS_DIR, index)).exists()
+        {
+            index += 1;
+        }
+
+        let file: String = format!("{}drawing_{}.txt", PATHS_DIR, index);
+
+        match save_to_txt(&file, &self.path)
+        {
+            Ok(()) => self.notify(format!("saved {}", file)),
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    fn apply_term_count(&mut self)
+    {
+        self.set_epicycles(self.coefficients[..self.term_count.min(self.coefficients.len())].to_vec());
+    }
+
+    // what is computed from the terms is only thrown away when they really change
+    fn set_epicycles(&mut self, epicycles: Vec<Epicycle>)
+    {
+        let same: bool = self.epicycles.len() == epicycles.len() && self.epicycles.iter().zip(&epicycles).all(|(a, b)| a.f == b.f && a.c0 == b.c0);
+
+        if !same
+        {
+            self.epicycles = epicycles;
+            self.curve = None;
+            self.error = None;
+        }
+    }
+
+    // add or remove terms, in steps that grow with the count
+    fn change_term_count(&mut self, direction: i32)
+    {
+        let step: usize = (self.term_count / 10).max(1);
+        let count: usize = if direction > 0 { self.term_count + step } else { self.term_count.saturating_sub(step) };
+
+        self.term_count = count.clamp(1, self.coefficients.len().max(1));
+        self.apply_term_count();
+
+        if self.convergence.is_some()
+        {
+            self.convergence = Some(Convergence::new(self.term_count));
+        }
+
+        // the drawing changes shape, start over with the new chain
+        self.set_persist(self.persist);
+        self.rebuild_trail();
+    }
+
+    fn toggle_convergence(&mut self)
+    {
+        if self.convergence.take().is_none()
+        {
+            self.convergence = Some(Convergence::new(self.term_count.min(self.coefficients.len())));
+            return;
+        }
+
+        self.apply_term_count();
+        self.rebuild_trail();
+    }
+
+    // fill the trail with the positions it would hold had the animation played up to the current time
+    fn rebuild_trail(&mut self)
+    {
+        self.shape.clear();
+
+        if !self.renderer.has_persisted()
+        {
+            self.persist_start = self.playback.t;
+            self.traced.clear();
+        }
+
+        if self.epicycles.is_empty()
+        {
+            return;
+        }
+
+        let step: f32 = self.playback.step();
+        let count: usize = self.shape.get_max_length();
+
+        for i in 0..count
+        {
+            let t: f32 = self.playback.t - step * (count - 1 - i) as f32;
+            self.shape.push(Epicycle::get_combined_position(&self.epicycles, t), t);
+        }
+    }
+
+    // single frames forward or back, the animation stays paused on them
+    fn step_frames(&mut self, count: i32)
+    {
+        self.playback.paused = true;
+        self.playback.step_frames(count);
+        self.rebuild_trail();
+    }
+
+    fn on_timeline(&self, x: i32, y: i32) -> bool
+    {
+        let (x, y) = self.renderer.window_to_canvas(x, y);
+
+        return self.renderer.timeline_hit(x, y);
+    }
+
+    fn scrub_to(&mut self, x: i32)
+    {
+        let (x, _) = self.renderer.window_to_canvas(x, 0);
+
+        self.playback.seek(self.renderer.timeline_progress(x));
+        self.rebuild_trail();
+    }
+
+    fn zoom_at_cursor(&mut self, factor: f32)
+    {
+        let mouse = self.event.mouse_state();
+        let (x, y) = self.renderer.window_to_canvas(mouse.x(), mouse.y());
+        let anchor: Complex = self.renderer.to_world(x, y);
+
+        self.renderer.camera.zoom_at(factor, &anchor);
+    }
+
+    // move the drawing along with the mouse while a button is held
+    fn drag_to(&mut self, x: i32, y: i32)
+    {
+        if let Some((from_x, from_y)) = self.drag_from
+        {
+            let (x0, y0) = self.renderer.window_to_canvas(from_x, from_y);
+            let (x1, y1) = self.renderer.window_to_canvas(x, y);
+            let offset: Complex = self.renderer.to_world(x0, y0) - self.renderer.to_world(x1, y1);
+
+            self.renderer.camera.pan(&offset);
+            self.renderer.camera.follow = false;
+            self.drag_from = Some((x, y));
+        }
+    }
+
     fn render(&mut self)
     {
-        self.renderer.clear(Color::RGB(5, 20, 60));
+        self.renderer.clear(self.theme.background);
             
-        self.renderer.draw_epicycles(&self.epicycles, self.t, Color::RGB(255, 255, 255));
+        if self.convergence.is_some()
+        {
+            // the faint original under the curve of the current terms
+            let curve: &Vec<Complex> = self.curve.get_or_insert_with(||
+            {
+                let mut curve: Vec<Complex> = reconstruct(&self.epicycles, CURVE_SAMPLE_COUNT);
+                curve.extend(curve.first().cloned());
+
+                return curve;
+            });
+
+            self.renderer.draw_lines(&self.centered_path, self.renderer.trail_width, self.theme.original);
+            self.renderer.draw_lines(curve, self.renderer.trail_width, self.theme.trail);
+        }
+
+        let circles: Vec<Color> = circle_colors(&self.epicycles, self.circle_coloring, &self.theme);
+        self.renderer.draw_epicycles(&self.epicycles, self.playback.t, &circles, self.theme.arm);
+
+        if self.convergence.is_none()
+        {
+            self.renderer.draw_persisted();
+
+            if self.drawing
+            {
+                for stroke in self.strokes.iter().chain(self.stroke.iter())
+                {
+                    self.renderer.draw_lines(stroke, self.renderer.trail_width, self.theme.sketch);
+                }
+            }
 
-        self.renderer.draw_trail(&self.shape, Color::RGB(255, 100, 0));
+            self.renderer.draw_styled_trail(&self.shape, &self.trail_style, self.theme.trail, Some(&self.path));
+        }
+
+        // the picture without the overlays
+        if self.screenshot
+        {
+            self.screenshot = false;
+            self.save_screenshot();
+        }
+
+        self.renderer.draw_timeline(self.playback.progress(), self.theme.timeline);
+
+        if self.hud
+        {
+            self.draw_hud();
+        }
+
+        if self.browser.is_some()
+        {
+            self.draw_browser();
+        }
+
+        if let Some((text, shown)) = &self.message
+        {
+            if shown.elapsed().as_secs_f32() < MESSAGE_DURATION
+            {
+                self.renderer.draw_message(text, self.theme.hud);
+            }
+        }
 
         self.renderer.display();
     }
 
+    fn draw_hud(&mut self)
+    {
+        let error: f32 = *self.error.get_or_insert_with(|| reconstruction_error(&self.path, &self.epicycles, ERROR_SAMPLE_COUNT));
+        let order: &str = match self.term_order { TermOrder::Magnitude => "size", TermOrder::Frequency => "frequency" };
+        let text: String = format!("{}\nepicycles {}/{} by {}\nt {:.3}\nspeed {:.2}x{}{}\nfps {:.0}\nerror {:.2}\ntheme {}",
+                                   self.file_name,
+                                   self.epicycles.len(), self.coefficients.len(), order,
+                                   self.playback.t,
+                                   self.playback.speed,
+                                   if self.playback.reversed { " reversed" } else { "" },
+                                   if self.playback.paused { " paused" } else { "" },
+                                   self.fps,
+                                   error,
+                                   self.theme.name);
+
+        let unit: f32 = self.renderer.overlay_unit();
+        self.renderer.draw_text(&text, 10.0 * unit, 10.0 * unit, 2.0, self.theme.hud);
+    }
+
+    fn save_screenshot(&mut self)
+    {
+        if let Err(e) = std::fs::create_dir_all(SCREENSHOT_DIR)
+        {
+            self.notify(Error::io(SCREENSHOT_DIR, e).to_string());
+            return;
+        }
+
+        let file: String = screenshot_file(SCREENSHOT_DIR);
+
+        match self.renderer.save_screenshot(&file)
+        {
+            Ok(()) => self.notify(format!("saved {}", file)),
+            Err(e) => self.notify(e.to_string()),
+        }
+    }
+
+    // the files around the selection on the right of the window, each with a preview of its shape
+    fn draw_browser(&mut self)
+    {
+        let browser: &Browser = match &self.browser { Some(browser) => browser, None => return };
+
+        let unit: f32 = self.renderer.overlay_unit();
+        let (width, _) = self.renderer.overlay_size();
+        let row_height: f32 = 48.0 * unit;
+        let thumbnail_size: f32 = 40.0 * unit;
+        let x: f32 = width - 360.0 * unit;
+        let mut y: f32 = 10.0 * unit;
+
+        if browser.files.is_empty()
+        {
+            self.renderer.draw_text(&format!("no shapes in {}", browser.dir), x, y, 2.0, self.theme.hud);
+            return;
+        }
+
+        for index in browser.visible(BROWSER_ROWS)
+        {
+            let color: Color = if index == browser.selected { self.theme.trail } else { self.theme.hud };
+
+            if let Some(points) = browser.thumbnail(index)
+            {
+                self.renderer.draw_thumbnail(points, x, y, thumbnail_size, color);
+            }
+
+            let name: String = std::path::Path::new(&browser.files[index]).file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+            self.renderer.draw_text(&name, x + thumbnail_size + 10.0 * unit, y + (thumbnail_size - 14.0 * unit) * 0.5, 2.0, color);
+
+            y += row_height;
+        }
+    }
+
     fn update(&mut self)
     {
-        self.shape.push(Epicycle::get_combined_position(&self.epicycles, self.t));
-                
-        if self.begin_draw
+        if let Some(convergence) = &mut self.convergence
         {
-            self.t += self.dt;
+            convergence.update(TICK);
+            let epicycles: Vec<Epicycle> = convergence.epicycles(&self.coefficients);
+            self.set_epicycles(epicycles);
         }
 
-        ::std::thread::sleep(core::time::Duration::new(0, 1_000_000_000u32 / self.max_framerate as u32));
+        let t: f32 = self.playback.t;
+        let position: Complex = Epicycle::get_combined_position(&self.epicycles, t);
+
+        if self.renderer.camera.follow
+        {
+            self.renderer.camera.follow_target(&position, TICK);
+        }
+                
+        if !self.playback.paused
+        {
+            self.shape.push(position.clone(), t);
+
+            if self.persist && !self.renderer.has_persisted()
+            {
+                self.traced.push(position);
+
+                // a whole period has been traced, from now on it stays on screen
+                if (t - self.persist_start).abs() >= 1.0
+                {
+                    self.renderer.persist_curve(std::mem::take(&mut self.traced), self.theme.trail);
+                }
+            }
+
+            self.playback.advance();
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,349 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use crate::dwc::complex::*;
+use crate::dwc::error::*;
+
+// little-endian layout:
+// "DWCP", version: u16, flags: u16, point count: u64,
+// if FLAG_STROKES: stroke count: u32 then the length of every stroke as u64,
+// then x, y for every point as f32 (or f64 with FLAG_DOUBLE)
+pub const BINARY_MAGIC: [u8; 4] = *b"DWCP";
+pub const BINARY_VERSION: u16 = 1;
+const FLAG_DOUBLE: u16 = 1;
+const FLAG_STROKES: u16 = 2;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct BinaryHeader
+{
+    pub version: u16,
+    pub double: bool, // coordinates stored as f64
+    pub count: u64,
+    pub strokes: Option<Vec<u64>> // length of every stroke, None for a single stroke
+}
+
+// binary header methods
+impl BinaryHeader
+{
+    pub fn new(strokes: &[Vec<Complex>], double: bool) -> BinaryHeader
+    {
+        let lengths: Vec<u64> = strokes.iter().map(|s| s.len() as u64).collect();
+
+        return BinaryHeader { version: BINARY_VERSION,
+                              double: double,
+                              count: lengths.iter().sum(),
+                              strokes: if strokes.len() > 1 { Some(lengths) } else { None } };
+    }
+}
+
+fn read_bytes<R: Read, const N: usize>(reader: &mut R, name: &str) -> Result<[u8; N], Error>
+{
+    let mut bytes: [u8; N] = [0; N];
+    reader.read_exact(&mut bytes).map_err(|e| Error::io(name, e))?;
+
+    return Ok(bytes);
+}
+
+// reads the header, then the points one at a time so big files never have to fit in memory twice
+pub struct PointReader<R: Read>
+{
+    reader: R,
+    name: String, // used in errors
+    header: BinaryHeader,
+    remaining: u64
+}
+
+// point reader methods
+impl<R: Read> PointReader<R>
+{
+    pub fn new(mut reader: R, name: &str) -> Result<PointReader<R>, Error>
+    {
+        if read_bytes::<R, 4>(&mut reader, name)? != BINARY_MAGIC
+        {
+            return Err(Error::format(name, "not a binary path file"));
+        }
+
+        let version: u16 = u16::from_le_bytes(read_bytes(&mut reader, name)?);
+
+        if version == 0 || version > BINARY_VERSION
+        {
+            return Err(Error::format(name, &format!("unsupported binary path version {}", version)));
+        }
+
+        let flags: u16 = u16::from_le_bytes(read_bytes(&mut reader, name)?);
+        let count: u64 = u64::from_le_bytes(read_bytes(&mut reader, name)?);
+
+        let strokes: Option<Vec<u64>> = if flags & FLAG_STROKES != 0
+        {
+            let stroke_count: u32 = u32::from_le_bytes(read_bytes(&mut reader, name)?);
+
+            // every stroke has a point, checked before the count is trusted to read the table
+            if u64::from(stroke_count) > count
+            {
+                return Err(Error::format(name, &format!("{} strokes for {} points", stroke_count, count)));
+            }
+
+            let mut lengths: Vec<u64> = Vec::new();
+
+            for _ in 0..stroke_count
+            {
+                lengths.push(u64::from_le_bytes(read_bytes(&mut reader, name)?));
+            }
+
+            let total: Option<u64> = lengths.iter().try_fold(0u64, |a, &l| a.checked_add(l));
+
+            if total != Some(count)
+            {
+                return Err(Error::format(name, "the stroke lengths do not add up to the point count"));
+            }
+
+            Some(lengths)
+        }
+        else
+        {
+            None
+        };
+
+        let header: BinaryHeader = BinaryHeader { version: version, double: flags & FLAG_DOUBLE != 0, count: count, strokes: strokes };
+
+        return Ok(PointReader { reader: reader, name: String::from(name), header: header, remaining: count });
+    }
+}
+
+impl<R: Read> PointReader<R>
+{
+    pub fn header(&self) -> &BinaryHeader
+    {
+        return &self.header;
+    }
+
+    fn read_point(&mut self) -> Result<Complex, Error>
+    {
+        if self.header.double
+        {
+            let x: f64 = f64::from_le_bytes(read_bytes(&mut self.reader, &self.name)?);
+            let y: f64 = f64::from_le_bytes(read_bytes(&mut self.reader, &self.name)?);
+
+            return Ok(Complex::new(x as f32, y as f32));
+        }
+
+        let x: f32 = f32::from_le_bytes(read_bytes(&mut self.reader, &self.name)?);
+        let y: f32 = f32::from_le_bytes(read_bytes(&mut self.reader, &self.name)?);
+
+        return Ok(Complex::new(x, y));
+    }
+
+    // every remaining point, split into the strokes of the header
+    pub fn read_strokes(mut self) -> Result<Vec<Vec<Complex>>, Error>
+    {
+        let lengths: Vec<u64> = self.header.strokes.clone().unwrap_or(Vec::from([self.header.count]));
+        let mut strokes: Vec<Vec<Complex>> = Vec::new();
+
+        for length in lengths
+        {
+            let stroke: Vec<Complex> = (0..length).map(|_| self.next().unwrap()).collect::<Result<Vec<Complex>, Error>>()?;
+            strokes.push(stroke);
+        }
+
+        return Ok(strokes);
+    }
+}
+
+impl<R: Read> Iterator for PointReader<R>
+{
+    type Item = Result<Complex, Error>;
+
+    fn next(&mut self) -> Option<Result<Complex, Error>>
+    {
+        if self.remaining == 0
+        {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        return Some(self.read_point());
+    }
+}
+
+// writes the header straight away, then the points as they come
+pub struct PointWriter<W: Write>
+{
+    writer: W,
+    name: String, // used in errors
+    double: bool,
+    remaining: u64
+}
+
+// point writer methods
+impl<W: Write> PointWriter<W>
+{
+    pub fn new(mut writer: W, name: &str, header: &BinaryHeader) -> Result<PointWriter<W>, Error>
+    {
+        let mut bytes: Vec<u8> = Vec::from(BINARY_MAGIC);
+        let flags: u16 = if header.double { FLAG_DOUBLE } else { 0 } | if header.strokes.is_some() { FLAG_STROKES } else { 0 };
+
+        bytes.extend(BINARY_VERSION.to_le_bytes());
+        bytes.extend(flags.to_le_bytes());
+        bytes.extend(header.count.to_le_bytes());
+
+        if let Some(lengths) = &header.strokes
+        {
+            bytes.extend((lengths.len() as u32).to_le_bytes());
+
+            for length in lengths
+            {
+                bytes.extend(length.to_le_bytes());
+            }
+        }
+
+        writer.write_all(&bytes).map_err(|e| Error::io(name, e))?;
+
+        return Ok(PointWriter { writer: writer, name: String::from(name), double: header.double, remaining: header.count });
+    }
+}
+
+impl<W: Write> PointWriter<W>
+{
+    pub fn write_point(&mut self, p: &Complex) -> Result<(), Error>
+    {
+        if self.remaining == 0
+        {
+            return Err(Error::format(&self.name, "more points written than announced in the header"));
+        }
+
+        self.remaining -= 1;
+
+        let result = if self.double
+        {
+            self.writer.write_all(&(p.real as f64).to_le_bytes()).and_then(|_| self.writer.write_all(&(p.img as f64).to_le_bytes()))
+        }
+        else
+        {
+            self.writer.write_all(&p.real.to_le_bytes()).and_then(|_| self.writer.write_all(&p.img.to_le_bytes()))
+        };
+
+        return result.map_err(|e| Error::io(&self.name, e));
+    }
+
+    // check every announced point was written and flush
+    pub fn finish(mut self) -> Result<W, Error>
+    {
+        if self.remaining != 0
+        {
+            return Err(Error::format(&self.name, &format!("{} points missing from the file", self.remaining)));
+        }
+
+        self.writer.flush().map_err(|e| Error::io(&self.name, e))?;
+
+        return Ok(self.writer);
+    }
+}
+
+pub fn load_from_binary(path: &str) -> Result<Vec<Vec<Complex>>, Error>
+{
+    let file: File = File::open(path).map_err(|e| Error::io(path, e))?;
+
+    return PointReader::new(BufReader::new(file), path)?.read_strokes();
+}
+
+pub fn save_to_binary(path: &str, strokes: &[Vec<Complex>], double: bool) -> Result<(), Error>
+{
+    let file: File = File::create(path).map_err(|e| Error::io(path, e))?;
+    let mut writer: PointWriter<BufWriter<File>> = PointWriter::new(BufWriter::new(file), path, &BinaryHeader::new(strokes, double))?;
+
+    for p in strokes.iter().flatten()
+    {
+        writer.write_point(p)?;
+    }
+
+    writer.finish()?;
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn write(strokes: &[Vec<Complex>], double: bool) -> Vec<u8>
+    {
+        let mut writer: PointWriter<Vec<u8>> = PointWriter::new(Vec::new(), "test", &BinaryHeader::new(strokes, double)).unwrap();
+
+        for p in strokes.iter().flatten()
+        {
+            writer.write_point(p).unwrap();
+        }
+
+        return writer.finish().unwrap();
+    }
+
+    #[test]
+    fn round_trip()
+    {
+        let strokes: Vec<Vec<Complex>> = Vec::from([Vec::from([Complex::new(1.0, 2.0), Complex::new(3.5, -4.0)]),
+                                                    Vec::from([Complex::new(0.25, 8.0)])]);
+
+        for double in [false, true]
+        {
+            let bytes: Vec<u8> = write(&strokes, double);
+            let reader: PointReader<&[u8]> = PointReader::new(&bytes[..], "test").unwrap();
+
+            assert_eq!(reader.header().count, 3);
+            assert_eq!(reader.read_strokes().unwrap(), strokes);
+        }
+
+        // magic, version, flags, count, then two f32 per point
+        assert_eq!(write(&Vec::from([Vec::from([Complex::new(1.0, 2.0)])]), false).len(), 4 + 2 + 2 + 8 + 8);
+    }
+
+    #[test]
+    fn invalid_files()
+    {
+        assert!(PointReader::new(&b"PNG0\x01\x00"[..], "test").is_err());
+
+        let mut bytes: Vec<u8> = write(&Vec::from([Vec::from([Complex::new(1.0, 2.0), Complex::new(3.0, 4.0)])]), false);
+        bytes.truncate(bytes.len() - 3);
+        assert!(PointReader::new(&bytes[..], "test").unwrap().read_strokes().is_err());
+    }
+
+    // magic, version and flags, the point count and the stroke table
+    fn stroke_header(count: u64, lengths: &[u64]) -> Vec<u8>
+    {
+        let mut bytes: Vec<u8> = Vec::from(BINARY_MAGIC);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&FLAG_STROKES.to_le_bytes());
+        bytes.extend_from_slice(&count.to_le_bytes());
+        bytes.extend_from_slice(&(lengths.len() as u32).to_le_bytes());
+
+        for length in lengths
+        {
+            bytes.extend_from_slice(&length.to_le_bytes());
+        }
+
+        return bytes;
+    }
+
+    #[test]
+    fn invalid_stroke_tables()
+    {
+        // the lengths overflow when added
+        let bytes: Vec<u8> = stroke_header(2, &[u64::MAX / 2 + 1, u64::MAX / 2 + 1]);
+        assert!(matches!(PointReader::new(&bytes[..], "test"), Err(Error::Format { .. })));
+
+        // more strokes than points
+        let bytes: Vec<u8> = stroke_header(1, &[1, 0]);
+        assert!(matches!(PointReader::new(&bytes[..], "test"), Err(Error::Format { .. })));
+
+        let bytes: Vec<u8> = stroke_header(0, &[0]);
+        assert!(matches!(PointReader::new(&bytes[..], "test"), Err(Error::Format { .. })));
+
+        let bytes: Vec<u8> = stroke_header(3, &[1, 2]);
+        assert_eq!(PointReader::new(&bytes[..], "test").unwrap().header().strokes, Some(Vec::from([1, 2])));
+    }
+}
\ No newline at end of file
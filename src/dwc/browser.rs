@@ -0,0 +1,181 @@
+use crate::dwc::complex::*;
+use crate::dwc::error::*;
+use crate::dwc::file_loader::*;
+use crate::dwc::path::*;
+
+use std::collections::HashMap;
+
+// every extension load_shape understands
+pub const PATH_EXTENSIONS: [&str; 9] = ["txt", "csv", "dwcp", "svg", "bmp", "pgm", "ppm", "pnm", "coef"];
+pub const THUMBNAIL_POINT_COUNT: usize = 200;
+
+// the shapes of a directory, one of them selected
+pub struct Browser
+{
+    pub dir: String,
+    pub files: Vec<String>, // sorted, with the directory
+    pub selected: usize,
+    thumbnails: HashMap<usize, Option<Vec<Complex>>>, // loaded by load_thumbnail, None for files that failed to load
+}
+
+// browser methods
+impl Browser
+{
+    pub fn new(dir: &str) -> Result<Browser, Error>
+    {
+        return Ok(Browser { dir: String::from(dir), files: list_paths(dir)?, selected: 0, thumbnails: HashMap::new() });
+    }
+}
+
+impl Browser
+{
+    pub fn selected_file(&self) -> Option<&String>
+    {
+        return self.files.get(self.selected);
+    }
+
+    // select the file if it is in the list
+    pub fn select_file(&mut self, file: &str)
+    {
+        let name = std::path::Path::new(file).file_name();
+
+        if let Some(index) = self.files.iter().position(|f| std::path::Path::new(f).file_name() == name)
+        {
+            self.selected = index;
+        }
+    }
+
+    // move the selection, wrapping around the ends of the list
+    pub fn select_next(&mut self, offset: i32)
+    {
+        if !self.files.is_empty()
+        {
+            self.selected = (self.selected as i32 + offset).rem_euclid(self.files.len() as i32) as usize;
+        }
+    }
+
+    // indices of `count` files around the selected one
+    pub fn visible(&self, count: usize) -> std::ops::Range<usize>
+    {
+        let start: usize = self.selected.saturating_sub(count / 2).min(self.files.len().saturating_sub(count));
+
+        return start..(start + count).min(self.files.len());
+    }
+
+    // the shape of a file, as from thumbnail_points, once it has been loaded
+    pub fn thumbnail(&self, index: usize) -> Option<&Vec<Complex>>
+    {
+        return self.thumbnails.get(&index)?.as_ref();
+    }
+
+    // load the first missing thumbnail among the `count` visible files, false when they are all loaded
+    // tracing a bitmap can take a while, so this is called once a frame rather than for every row drawn
+    pub fn load_thumbnail(&mut self, count: usize, trace: ImageTrace) -> bool
+    {
+        let index: usize = match self.visible(count).find(|i| !self.thumbnails.contains_key(i)) { Some(index) => index, None => return false };
+        let shape: Option<Vec<Complex>> = load_shape_traced(&self.files[index], trace).ok().map(|path| thumbnail_points(&path, THUMBNAIL_POINT_COUNT));
+
+        self.thumbnails.insert(index, shape);
+
+        return true;
+    }
+}
+
+// the files of `dir` with a supported extension, sorted by name
+pub fn list_paths(dir: &str) -> Result<Vec<String>, Error>
+{
+    let mut files: Vec<String> = Vec::new();
+
+    for entry in std::fs::read_dir(dir).map_err(|e| Error::io(dir, e))?
+    {
+        let path: std::path::PathBuf = entry.map_err(|e| Error::io(dir, e))?.path();
+        let file: String = path.to_string_lossy().to_string();
+
+        if path.is_file() && PATH_EXTENSIONS.iter().any(|extension| has_extension(&file, extension))
+        {
+            files.push(file);
+        }
+    }
+
+    files.sort();
+
+    return Ok(files);
+}
+
+// `count` points of the shape, centred and scaled to fit in -1..1 on both axes
+pub fn thumbnail_points(path: &[Complex], count: usize) -> Vec<Complex>
+{
+    let bounds: Bounds = match Bounds::from_points(path) { Some(bounds) => bounds, None => return Vec::new() };
+    let center: Complex = bounds.center();
+    let half_size: f32 = bounds.width().max(bounds.height()) * 0.5;
+    let scale: f32 = if half_size > 0.0 { 1.0 / half_size } else { 0.0 };
+
+    let mut points: Vec<Complex> = resample(path, count).iter().map(|p| (p - &center) * scale).collect();
+    points.extend(points.first().cloned());
+
+    return points;
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn directory()
+    {
+        let dir: std::path::PathBuf = std::env::temp_dir().join(format!("dwc_browser_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for name in ["b.txt", "a.SVG", "notes.md", "c.dwcp"]
+        {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let mut browser: Browser = Browser::new(&dir.to_string_lossy()).unwrap();
+        let names: Vec<String> = browser.files.iter().map(|f| std::path::Path::new(f).file_name().unwrap().to_string_lossy().to_string()).collect();
+        assert_eq!(names, vec!["a.SVG", "b.txt", "c.dwcp"]);
+
+        browser.select_file("./somewhere/else/c.dwcp");
+        assert_eq!(browser.selected, 2);
+        browser.select_next(1);
+        assert_eq!(browser.selected, 0);
+        browser.select_next(-1);
+        assert_eq!(browser.selected, 2);
+
+        // one file at a time, empty files do not load
+        assert!(browser.load_thumbnail(5, ImageTrace::SingleLine));
+        assert_eq!(browser.thumbnails.len(), 1);
+        assert!(browser.load_thumbnail(5, ImageTrace::SingleLine));
+        assert!(browser.load_thumbnail(5, ImageTrace::SingleLine));
+        assert!(!browser.load_thumbnail(5, ImageTrace::SingleLine));
+        assert!(browser.thumbnail(1).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(Browser::new(&dir.to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn visible_window()
+    {
+        let mut browser: Browser = Browser { dir: String::new(), files: (0..10).map(|i| i.to_string()).collect(), selected: 0, thumbnails: HashMap::new() };
+
+        assert_eq!(browser.visible(5), 0..5);
+        browser.selected = 5;
+        assert_eq!(browser.visible(5), 3..8);
+        browser.selected = 9;
+        assert_eq!(browser.visible(5), 5..10);
+        assert_eq!(browser.visible(20), 0..10);
+    }
+
+    #[test]
+    fn thumbnail_fits()
+    {
+        let path: Vec<Complex> = vec![Complex::new(10.0, 5.0), Complex::new(14.0, 5.0), Complex::new(14.0, 7.0), Complex::new(10.0, 7.0)];
+        let points: Vec<Complex> = thumbnail_points(&path, 40);
+
+        assert_eq!(points.len(), 41);
+        assert!(points.iter().all(|p| p.real.abs() <= 1.0 + 1e-5 && p.img.abs() <= 0.5 + 1e-5));
+        assert!(points.iter().any(|p| p.real > 0.99));
+    }
+}
\ No newline at end of file
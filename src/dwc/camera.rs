@@ -0,0 +1,104 @@
+use crate::dwc::complex::*;
+
+pub const MIN_ZOOM: f32 = 0.05;
+pub const MAX_ZOOM: f32 = 1000.0;
+
+// which part of the drawing is on screen
+pub struct Camera
+{
+    pub center: Complex, // drawing coordinates shown at the centre of the screen
+    pub zoom: f32,
+    pub follow: bool, // track the tip of the epicycles
+    pub smoothing: f32 // how much of the way to the tip is covered every second when following
+}
+
+// camera methods
+impl Camera
+{
+    pub fn new() -> Camera
+    {
+        return Camera { center: Complex::new(0.0, 0.0), zoom: 1.0, follow: false, smoothing: 0.99 };
+    }
+}
+
+impl Default for Camera
+{
+    fn default() -> Camera
+    {
+        return Camera::new();
+    }
+}
+
+impl Camera
+{
+    // zoom by `factor` while `anchor` (in drawing coordinates) stays at the same place on screen
+    pub fn zoom_at(&mut self, factor: f32, anchor: &Complex)
+    {
+        let zoom: f32 = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+
+        self.center = anchor - (anchor - &self.center) * (self.zoom / zoom);
+        self.zoom = zoom;
+    }
+
+    pub fn pan(&mut self, offset: &Complex)
+    {
+        self.center += offset;
+    }
+
+    // ease the centre towards the target, frame rate independent
+    pub fn follow_target(&mut self, target: &Complex, elapsed: f32)
+    {
+        let amount: f32 = 1.0 - (1.0 - self.smoothing.clamp(0.0, 0.999_999)).powf(elapsed);
+
+        self.center += (target - &self.center) * amount;
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.center = Complex::new(0.0, 0.0);
+        self.zoom = 1.0;
+        self.follow = false;
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_the_anchor_in_place()
+    {
+        let mut camera: Camera = Camera::new();
+        let anchor: Complex = Complex::new(10.0, 5.0);
+
+        camera.zoom_at(4.0, &anchor);
+
+        // the anchor's offset from the centre shrinks with the zoom, so it stays put on screen
+        let offset: Complex = (&anchor - &camera.center) * camera.zoom;
+        assert!((offset.real - 10.0).abs() < 0.001 && (offset.img - 5.0).abs() < 0.001);
+        assert_eq!(camera.zoom, 4.0);
+    }
+
+    #[test]
+    fn zoom_is_clamped()
+    {
+        let mut camera: Camera = Camera::new();
+        camera.zoom_at(1e9, &Complex::new(0.0, 0.0));
+
+        assert_eq!(camera.zoom, MAX_ZOOM);
+    }
+
+    #[test]
+    fn follow_converges()
+    {
+        let mut camera: Camera = Camera::new();
+
+        for _ in 0..120
+        {
+            camera.follow_target(&Complex::new(100.0, 0.0), 1.0 / 60.0);
+        }
+
+        assert!((camera.center.real - 100.0).abs() < 1.0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,363 @@
+extern crate sdl2;
+
+use crate::dwc::contour::*;
+use crate::dwc::file_loader::*;
+use crate::dwc::settings::*;
+use crate::dwc::theme::*;
+use crate::dwc::trail_style::*;
+
+use sdl2::pixels::Color;
+
+pub const HELP: &str = "\
+Draw a path with epicycles
+
+usage: draw_with_circles [options] [file]
+
+file is a .txt or .csv, .dwcp, .svg, .bmp, .pgm or .ppm path, or a .coef file of coefficients
+(default ./paths/default.txt), files with other extensions are recognised by their content
+
+options:
+  -n, --terms <count>          number of epicycles (default 298)
+      --rate <periods>         periods drawn per second (default 0.0667)
+      --trail <length>         length of the trail, in ticks of 1/60 s (default 880)
+      --size <width>x<height>  window size (default 1000x800)
+      --scale <factor>         fixed drawing scale instead of fitting the window
+      --fps <count>            frame rate limit without vsync, 0 for none (default 60)
+      --no-vsync               do not wait for the screen refresh
+      --trace <mode>           how bitmaps are traced: largest, all or single-line (default largest)
+      --theme <name>           dark, light, print, high-contrast or colour-blind (default dark)
+      --circles <mode>         circle colours: uniform, magnitude or frequency (default uniform)
+      --trail-style <mode>     trail colours: solid, hue, speed, curvature or error (default solid)
+      --no-trail-fade          keep old parts of the trail opaque
+      --trail-color <color>    colour of the drawing, as #rrggbb or r,g,b, instead of the theme's
+      --circle-color <color>   colour of the circles
+      --background <color>     background colour
+      --trail-width <pixels>   width of the drawing (default 2)
+      --arm-width <pixels>     width of the arms (default 1)
+      --circle-width <pixels>  width of the circles (default 1)
+      --no-antialiasing        draw one pixel wide lines without antialiasing
+      --config <file>          read settings from a key = value file (default ./draw_with_circles.conf when present),
+                               the other options override it
+  -o, --output <mode>          window, fullscreen or coefficients (default window)
+  -h, --help                   show this message";
+
+// what the command line asks for
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum Command
+{
+    Help,
+    Run(Settings),
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String>
+{
+    return value.parse::<T>().map_err(|_| format!("invalid value '{}' for {}", value, option));
+}
+
+// "#rrggbb" or "r,g,b"
+pub fn parse_color(value: &str) -> Result<Color, String>
+{
+    let invalid = || format!("invalid colour '{}', expected #rrggbb or r,g,b", value);
+
+    if let Some(hex) = value.strip_prefix('#')
+    {
+        if hex.len() != 6 || !hex.is_ascii()
+        {
+            return Err(invalid());
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+
+        return Ok(Color::RGB(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    let channels: Vec<u8> = value.split(',').map(|c| c.trim().parse::<u8>().map_err(|_| invalid())).collect::<Result<Vec<u8>, String>>()?;
+
+    if channels.len() != 3
+    {
+        return Err(invalid());
+    }
+
+    return Ok(Color::RGB(channels[0], channels[1], channels[2]));
+}
+
+// stroke widths in pixels
+fn parse_width(option: &str, value: &str) -> Result<f32, String>
+{
+    let width: f32 = parse_number(option, value)?;
+
+    if !width.is_finite() || width <= 0.0
+    {
+        return Err(format!("invalid width '{}' for {}, expected a positive number of pixels", value, option));
+    }
+
+    return Ok(width);
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String>
+{
+    let invalid = || format!("invalid window size '{}', expected <width>x<height>", value);
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+
+    let width: u32 = width.parse::<u32>().map_err(|_| invalid())?;
+    let height: u32 = height.parse::<u32>().map_err(|_| invalid())?;
+
+    if width == 0 || height == 0
+    {
+        return Err(invalid());
+    }
+
+    return Ok((width, height));
+}
+
+// change one setting by name, for both the command line and config files (which may write '_' for '-')
+pub fn set_option(settings: &mut Settings, key: &str, value: &str) -> Result<(), String>
+{
+    let key: String = key.replace('_', "-");
+    let option: &str = &key;
+
+    match option
+    {
+        "file" => settings.file = String::from(value),
+        "terms" => settings.term_count = parse_number(option, value)?,
+        "rate" => settings.rate = parse_number(option, value)?,
+        "trail" => settings.trail_length = parse_number(option, value)?,
+        "size" => (settings.window_width, settings.window_height) = parse_size(value)?,
+        "width" => settings.window_width = parse_number(option, value)?,
+        "height" => settings.window_height = parse_number(option, value)?,
+        "scale" => settings.scale = if value == "auto" { None } else { Some(parse_number(option, value)?) },
+        "fps" => settings.max_framerate = parse_number(option, value)?,
+        "vsync" => settings.vsync = parse_number(option, value)?,
+        "trace" =>
+        {
+            settings.image_trace = match value
+            {
+                "largest" => ImageTrace::Contours(ContourSelection::Largest),
+                "all" => ImageTrace::Contours(ContourSelection::All),
+                "single-line" => ImageTrace::SingleLine,
+                _ => return Err(format!("invalid trace mode '{}', expected largest, all or single-line", value)),
+            };
+        },
+        "theme" => settings.theme = theme_index(value).ok_or_else(|| format!("unknown theme '{}'", value))?,
+        "circles" =>
+        {
+            settings.circles = match value
+            {
+                "uniform" => CircleColoring::Uniform,
+                "magnitude" => CircleColoring::Magnitude,
+                "frequency" => CircleColoring::Frequency,
+                _ => return Err(format!("invalid circle colouring '{}', expected uniform, magnitude or frequency", value)),
+            };
+        },
+        "trail-style" =>
+        {
+            settings.trail_style.coloring = match value
+            {
+                "solid" => TrailColoring::Solid,
+                "hue" => TrailColoring::Hue,
+                "speed" => TrailColoring::Speed,
+                "curvature" => TrailColoring::Curvature,
+                "error" => TrailColoring::Error,
+                _ => return Err(format!("invalid trail style '{}', expected solid, hue, speed, curvature or error", value)),
+            };
+        },
+        "trail-fade" => settings.trail_style.fade = parse_number(option, value)?,
+        "trail-color" => settings.trail_color = Some(parse_color(value)?),
+        "circle-color" => settings.circle_color = Some(parse_color(value)?),
+        "background" => settings.background_color = Some(parse_color(value)?),
+        "antialiasing" => settings.antialiasing = parse_number(option, value)?,
+        "trail-width" => settings.trail_width = parse_width(option, value)?,
+        "arm-width" => settings.arm_width = parse_width(option, value)?,
+        "circle-width" => settings.circle_width = parse_width(option, value)?,
+        "output" =>
+        {
+            settings.output = match value
+            {
+                "window" => OutputMode::Window,
+                "fullscreen" => OutputMode::Fullscreen,
+                "coefficients" => OutputMode::Coefficients,
+                _ => return Err(format!("invalid output mode '{}', expected window, fullscreen or coefficients", value)),
+            };
+        },
+        _ => return Err(format!("unknown setting '{}'", key)),
+    }
+
+    return Ok(());
+}
+
+// the config file named by --config, if any
+pub fn config_path(args: &[String]) -> Option<String>
+{
+    let index: usize = args.iter().position(|a| a == "--config")?;
+
+    return args.get(index + 1).cloned();
+}
+
+// the arguments without the program name, applied over `settings` (the defaults or a config file)
+pub fn parse_args(args: &[String], mut settings: Settings) -> Result<Command, String>
+{
+    let mut file: Option<String> = None;
+    let mut i: usize = 0;
+
+    while i < args.len()
+    {
+        let option: &str = &args[i];
+        i += 1;
+
+        // options that take no value
+        match option
+        {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--no-vsync" =>
+            {
+                settings.vsync = false;
+                continue;
+            },
+            "--no-trail-fade" =>
+            {
+                settings.trail_style.fade = false;
+                continue;
+            },
+            "--no-antialiasing" =>
+            {
+                settings.antialiasing = false;
+                continue;
+            },
+            _ => {},
+        }
+
+        if !option.starts_with('-') || option == "-"
+        {
+            if file.is_some()
+            {
+                return Err(format!("unexpected argument '{}', only one file can be drawn", option));
+            }
+
+            file = Some(String::from(option));
+            continue;
+        }
+
+        let value: &str = match args.get(i)
+        {
+            Some(value) => value,
+            None => return Err(format!("missing value for {}", option)),
+        };
+        i += 1;
+
+        // short options stand for their long form
+        let key: &str = match option
+        {
+            "-n" => "terms",
+            "-o" => "output",
+            "--config" => continue, // read before the other options, see load_settings
+            _ => option.trim_start_matches('-'),
+        };
+
+        set_option(&mut settings, key, value)?;
+    }
+
+    if settings.term_count == 0
+    {
+        return Err(String::from("at least one term is needed"));
+    }
+
+    if let Some(file) = file
+    {
+        settings.file = file;
+    }
+
+    return Ok(Command::Run(settings));
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn args(line: &str) -> Vec<String>
+    {
+        return line.split_whitespace().map(String::from).collect();
+    }
+
+    #[test]
+    fn defaults()
+    {
+        assert_eq!(parse_args(&Vec::new(), Settings::default()), Ok(Command::Run(Settings::default())));
+        assert_eq!(parse_args(&args("--terms 5 --help"), Settings::default()), Ok(Command::Help));
+    }
+
+    #[test]
+    fn options()
+    {
+        let settings: Settings = match parse_args(&args("shape.svg -n 40 --rate 0.5 --size 640x480 --trail-color #ff8000 --theme print -o coefficients"), Settings::default()).unwrap()
+        {
+            Command::Run(settings) => settings,
+            Command::Help => panic!("help was not asked for"),
+        };
+
+        assert_eq!(settings.file, "shape.svg");
+        assert_eq!(settings.term_count, 40);
+        assert_eq!(settings.rate, 0.5);
+        assert_eq!((settings.window_width, settings.window_height), (640, 480));
+        assert_eq!(settings.trail_color, Some(Color::RGB(255, 128, 0)));
+        assert_eq!(settings.theme, 2);
+        assert_eq!(settings.output, OutputMode::Coefficients);
+    }
+
+    #[test]
+    fn strokes()
+    {
+        let settings: Settings = match parse_args(&args("--trail-width 3.5 --arm-width 2 --circle-width 0.5 --no-antialiasing"), Settings::default()).unwrap()
+        {
+            Command::Run(settings) => settings,
+            Command::Help => panic!("help was not asked for"),
+        };
+
+        assert_eq!((settings.trail_width, settings.arm_width, settings.circle_width), (3.5, 2.0, 0.5));
+        assert!(!settings.antialiasing);
+    }
+
+    #[test]
+    fn trail_styles()
+    {
+        let styles: [(&str, TrailColoring); 5] = [("solid", TrailColoring::Solid),
+                                                  ("hue", TrailColoring::Hue),
+                                                  ("speed", TrailColoring::Speed),
+                                                  ("curvature", TrailColoring::Curvature),
+                                                  ("error", TrailColoring::Error)];
+
+        for (name, coloring) in styles
+        {
+            match parse_args(&args(&format!("--trail-style {}", name)), Settings::default()).unwrap()
+            {
+                Command::Run(settings) => assert_eq!(settings.trail_style, TrailStyle::new(coloring, true)),
+                Command::Help => panic!("help was not asked for"),
+            }
+        }
+
+        match parse_args(&args("--no-trail-fade --trail-style hue"), Settings::default()).unwrap()
+        {
+            Command::Run(settings) => assert_eq!(settings.trail_style, TrailStyle::new(TrailColoring::Hue, false)),
+            Command::Help => panic!("help was not asked for"),
+        }
+
+        assert!(parse_args(&args("--trail-style plaid"), Settings::default()).is_err());
+    }
+
+    #[test]
+    fn errors()
+    {
+        assert!(parse_args(&args("--terms"), Settings::default()).is_err());
+        assert!(parse_args(&args("--terms many"), Settings::default()).is_err());
+        assert!(parse_args(&args("--size 640"), Settings::default()).is_err());
+        assert!(parse_args(&args("--frobnicate 1"), Settings::default()).is_err());
+        assert!(parse_args(&args("--dt 0.001"), Settings::default()).is_err());
+        assert!(parse_args(&args("--theme neon"), Settings::default()).is_err());
+        assert!(parse_args(&args("--trail-width 0"), Settings::default()).is_err());
+        assert!(parse_args(&args("--arm-width -1"), Settings::default()).is_err());
+        assert!(parse_args(&args("a.txt b.txt"), Settings::default()).is_err());
+        assert!(parse_color("1,2").is_err());
+        assert_eq!(parse_color("1, 2, 3"), Ok(Color::RGB(1, 2, 3)));
+    }
+}
\ No newline at end of file
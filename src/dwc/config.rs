@@ -0,0 +1,123 @@
+use crate::dwc::cli::*;
+use crate::dwc::error::*;
+use crate::dwc::settings::*;
+
+// read when no --config is given, if it exists
+pub const DEFAULT_CONFIG: &str = "./draw_with_circles.conf";
+
+// "key = value" lines with the names of the long options, e.g.
+//   terms = 120
+//   trail_color = "#ff8000"
+// lines starting with # are comments, values may be quoted as in TOML
+pub fn parse_config(contents: &str, file: &str, settings: &mut Settings) -> Result<(), Error>
+{
+    for (index, line) in contents.lines().enumerate()
+    {
+        let trimmed: &str = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#')
+        {
+            continue;
+        }
+
+        let indent: usize = line.len() - line.trim_start().len();
+
+        let (key, value) = match trimmed.split_once('=')
+        {
+            Some(pair) => pair,
+            None => return Err(Error::parse(file, index + 1, indent + 1, "expected key = value")),
+        };
+
+        let value_column: usize = indent + key.len() + 2 + (value.len() - value.trim_start().len());
+        let value: &str = value.trim();
+        let value: &str = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+
+        set_option(settings, key.trim(), value).map_err(|e| Error::parse(file, index + 1, value_column, &e))?;
+    }
+
+    return Ok(());
+}
+
+pub fn load_config(file: &str, settings: &mut Settings) -> Result<(), Error>
+{
+    let contents: String = std::fs::read_to_string(file).map_err(|e| Error::io(file, e))?;
+
+    return parse_config(&contents, file, settings);
+}
+
+// the defaults, then the config file, then the command line (without the program name)
+pub fn load_settings(args: &[String]) -> Result<Command, Error>
+{
+    let mut settings: Settings = Settings::default();
+
+    let config: Option<String> = match config_path(args)
+    {
+        Some(file) => Some(file),
+        None if std::path::Path::new(DEFAULT_CONFIG).is_file() => Some(String::from(DEFAULT_CONFIG)),
+        None => None,
+    };
+
+    if let Some(file) = config
+    {
+        load_config(&file, &mut settings)?;
+    }
+
+    return parse_args(args, settings).map_err(Error::Usage);
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    use crate::dwc::file_loader::*;
+
+    #[test]
+    fn config_file()
+    {
+        let mut settings: Settings = Settings::default();
+        let contents: &str = "# a comment\n\nterms = 40\ntrail_color = \"#ff8000\"\n  scale = 2.5\ntrace = single-line\n";
+
+        parse_config(contents, "test.conf", &mut settings).unwrap();
+
+        assert_eq!(settings.term_count, 40);
+        assert_eq!(settings.trail_color, Some(sdl2::pixels::Color::RGB(255, 128, 0)));
+        assert_eq!(settings.scale, Some(2.5));
+        assert_eq!(settings.image_trace, ImageTrace::SingleLine);
+    }
+
+    #[test]
+    fn config_errors()
+    {
+        let mut settings: Settings = Settings::default();
+
+        match parse_config("terms = 4\nterms 5\n", "test.conf", &mut settings)
+        {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (2, 1)),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match parse_config("fps = fast\n", "test.conf", &mut settings)
+        {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (1, 7)),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(parse_config("frobnicate = 1\n", "test.conf", &mut settings).is_err());
+    }
+
+    #[test]
+    fn command_line_overrides()
+    {
+        let mut settings: Settings = Settings::default();
+        parse_config("terms = 40\nfps = 30\n", "test.conf", &mut settings).unwrap();
+
+        let args: Vec<String> = vec![String::from("--terms"), String::from("12"), String::from("--config"), String::from("test.conf")];
+
+        match parse_args(&args, settings).unwrap()
+        {
+            Command::Run(settings) => assert_eq!((settings.term_count, settings.max_framerate), (12, 30)),
+            Command::Help => panic!("unexpected help"),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use crate::dwc::complex::*;
+use crate::dwc::image_loader::*;
+use crate::dwc::error::*;
+
+// which contours of a traced image make up the shape
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum ContourSelection
+{
+    Largest, // the contour enclosing the biggest area
+    All,
+}
+
+// threshold that best separates the dark and light pixels (otsu's method)
+pub fn otsu_threshold(image: &GrayImage) -> f32
+{
+    let mut histogram: [usize; 256] = [0; 256];
+
+    for value in &image.pixels
+    {
+        histogram[(value.clamp(0.0, 1.0) * 255.0) as usize] += 1;
+    }
+
+    let total: f32 = image.pixels.len() as f32;
+    let sum: f32 = histogram.iter().enumerate().map(|(i, count)| i as f32 * *count as f32).sum();
+
+    let mut best_threshold: usize = 127;
+    let mut best_variance: f32 = 0.0;
+    let mut background_count: f32 = 0.0;
+    let mut background_sum: f32 = 0.0;
+
+    for (i, count) in histogram.iter().enumerate()
+    {
+        background_count += *count as f32;
+        background_sum += i as f32 * *count as f32;
+
+        let foreground_count: f32 = total - background_count;
+
+        if background_count == 0.0 || foreground_count == 0.0
+        {
+            continue;
+        }
+
+        let background_mean: f32 = background_sum / background_count;
+        let foreground_mean: f32 = (sum - background_sum) / foreground_count;
+        let variance: f32 = background_count * foreground_count * (background_mean - foreground_mean).powi(2);
+
+        if variance > best_variance
+        {
+            best_variance = variance;
+            best_threshold = i;
+        }
+    }
+
+    // the iso-line sits between the two classes
+    return (best_threshold as f32 + 0.5) / 255.0;
+}
+
+// edge id, its two corners and the values at those corners
+type CellEdge = (i64, (i64, i64), (i64, i64), f32, f32);
+
+// pixel value with a one pixel border of background around the image, so every contour closes
+fn padded_value(image: &GrayImage, x: i64, y: i64) -> f32
+{
+    if x < 1 || y < 1 || x > image.width as i64 || y > image.height as i64
+    {
+        return 1.0;
+    }
+
+    return image.get((x - 1) as usize, (y - 1) as usize);
+}
+
+// extract the iso-contours of the image at `threshold` with marching squares
+// dark regions (below the threshold) are the inside, points are in pixel units with y pointing up
+pub fn trace_contours(image: &GrayImage, threshold: f32) -> Vec<Vec<Complex>>
+{
+    let width: i64 = image.width as i64 + 2;
+    let height: i64 = image.height as i64 + 2;
+
+    // edges are identified by their cell corner and direction so neighbouring cells share points
+    let edge_id = |x: i64, y: i64, vertical: bool| -> i64 { (y * width + x) * 2 + vertical as i64 };
+    let mut positions: HashMap<i64, Complex> = HashMap::new();
+    let mut segments: Vec<(i64, i64)> = Vec::new();
+
+    for y in 0..height - 1
+    {
+        for x in 0..width - 1
+        {
+            // corners in clockwise order: top left, top right, bottom right, bottom left
+            let values: [f32; 4] = [padded_value(image, x, y), padded_value(image, x + 1, y),
+                                    padded_value(image, x + 1, y + 1), padded_value(image, x, y + 1)];
+            let inside: Vec<bool> = values.iter().map(|v| *v < threshold).collect();
+
+            let case: usize = (inside[0] as usize) << 3 | (inside[1] as usize) << 2 | (inside[2] as usize) << 1 | inside[3] as usize;
+
+            if case == 0 || case == 15
+            {
+                continue;
+            }
+
+            // cell edges: top, right, bottom, left
+            let edges: [CellEdge; 4] =
+            [
+                (edge_id(x, y, false), (x, y), (x + 1, y), values[0], values[1]),
+                (edge_id(x + 1, y, true), (x + 1, y), (x + 1, y + 1), values[1], values[2]),
+                (edge_id(x, y + 1, false), (x, y + 1), (x + 1, y + 1), values[3], values[2]),
+                (edge_id(x, y, true), (x, y), (x, y + 1), values[0], values[3]),
+            ];
+
+            let pairs: Vec<(usize, usize)> = match case
+            {
+                1 | 14 => Vec::from([(2, 3)]),
+                2 | 13 => Vec::from([(1, 2)]),
+                3 | 12 => Vec::from([(1, 3)]),
+                4 | 11 => Vec::from([(0, 1)]),
+                6 | 9 => Vec::from([(0, 2)]),
+                7 | 8 => Vec::from([(0, 3)]),
+                // saddles, the average of the corners decides which diagonal is connected
+                5 | 10 =>
+                {
+                    let connected: bool = values.iter().sum::<f32>() * 0.25 < threshold;
+
+                    // cut off the outside corners, either top right and bottom left or top left and bottom right
+                    if connected == (case == 10) { Vec::from([(0, 1), (2, 3)]) } else { Vec::from([(0, 3), (1, 2)]) }
+                },
+                _ => Vec::new(),
+            };
+
+            for (a, b) in pairs
+            {
+                for edge in [edges[a], edges[b]]
+                {
+                    let (id, p0, p1, v0, v1) = edge;
+                    let s: f32 = if v1 != v0 { ((threshold - v0) / (v1 - v0)).clamp(0.0, 1.0) } else { 0.5 };
+
+                    // move back into image coordinates (the padding shifted everything by one)
+                    let px: f32 = p0.0 as f32 + (p1.0 - p0.0) as f32 * s - 1.0;
+                    let py: f32 = p0.1 as f32 + (p1.1 - p0.1) as f32 * s - 1.0;
+
+                    positions.entry(id).or_insert(Complex::new(px, -py));
+                }
+
+                segments.push((edges[a].0, edges[b].0));
+            }
+        }
+    }
+
+    return link_segments(&segments, &positions);
+}
+
+// chain segments that share an edge point into closed contours
+fn link_segments(segments: &[(i64, i64)], positions: &HashMap<i64, Complex>) -> Vec<Vec<Complex>>
+{
+    let mut neighbours: HashMap<i64, Vec<usize>> = HashMap::new();
+
+    for (i, segment) in segments.iter().enumerate()
+    {
+        neighbours.entry(segment.0).or_default().push(i);
+        neighbours.entry(segment.1).or_default().push(i);
+    }
+
+    let mut used: Vec<bool> = vec![false; segments.len()];
+    let mut contours: Vec<Vec<Complex>> = Vec::new();
+
+    for start in 0..segments.len()
+    {
+        if used[start]
+        {
+            continue;
+        }
+
+        used[start] = true;
+
+        let mut contour: Vec<Complex> = Vec::from([positions[&segments[start].0].clone()]);
+        let mut current: i64 = segments[start].1;
+
+        while current != segments[start].0
+        {
+            contour.push(positions[&current].clone());
+
+            let next: Option<usize> = neighbours[&current].iter().cloned().find(|i| !used[*i]);
+
+            match next
+            {
+                Some(i) =>
+                {
+                    used[i] = true;
+                    current = if segments[i].0 == current { segments[i].1 } else { segments[i].0 };
+                },
+                None => break,
+            }
+        }
+
+        if contour.len() > 2
+        {
+            contours.push(contour);
+        }
+    }
+
+    return contours;
+}
+
+pub fn contour_area(contour: &[Complex]) -> f32
+{
+    let mut area: f32 = 0.0;
+
+    for i in 0..contour.len()
+    {
+        let a: &Complex = &contour[i];
+        let b: &Complex = &contour[(i + 1) % contour.len()];
+
+        area += a.real * b.img - b.real * a.img;
+    }
+
+    return (area * 0.5).abs();
+}
+
+pub fn select_contours(contours: Vec<Vec<Complex>>, selection: ContourSelection) -> Vec<Vec<Complex>>
+{
+    if selection == ContourSelection::All
+    {
+        return contours;
+    }
+
+    return contours.into_iter()
+                   .max_by(|a, b| contour_area(a).total_cmp(&contour_area(b)))
+                   .into_iter()
+                   .collect();
+}
+
+// trace a bitmap into strokes, thresholding at otsu's level
+pub fn load_from_image(path: &str, selection: ContourSelection) -> Result<Vec<Vec<Complex>>, Error>
+{
+    let image: GrayImage = load_image(path)?;
+    let contours: Vec<Vec<Complex>> = trace_contours(&image, otsu_threshold(&image));
+
+    if contours.is_empty()
+    {
+        return Err(Error::EmptyPath(String::from(path)));
+    }
+
+    return Ok(select_contours(contours, selection));
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn square_image() -> GrayImage
+    {
+        // 4x4 dark square in the middle of an 8x8 white image
+        let mut image: GrayImage = GrayImage::new(8, 8);
+
+        for y in 0..8
+        {
+            for x in 0..8
+            {
+                let inside: bool = (2..6).contains(&x) && (2..6).contains(&y);
+                image.set(x, y, if inside { 0.0 } else { 1.0 });
+            }
+        }
+
+        return image;
+    }
+
+    #[test]
+    fn square_contour()
+    {
+        let contours: Vec<Vec<Complex>> = trace_contours(&square_image(), 0.5);
+
+        assert_eq!(contours.len(), 1);
+
+        // the iso-line passes half way between the dark and light pixels
+        let area: f32 = contour_area(&contours[0]);
+        assert!(area > 15.0 && area < 17.0);
+    }
+
+    #[test]
+    fn threshold_between_classes()
+    {
+        let threshold: f32 = otsu_threshold(&square_image());
+
+        assert!(threshold > 0.0 && threshold < 1.0);
+    }
+
+    #[test]
+    fn largest_contour()
+    {
+        let mut image: GrayImage = square_image();
+        image.set(0, 0, 0.0);
+
+        let contours: Vec<Vec<Complex>> = trace_contours(&image, 0.5);
+        assert_eq!(contours.len(), 2);
+
+        let largest: Vec<Vec<Complex>> = select_contours(contours, ContourSelection::Largest);
+        assert_eq!(largest.len(), 1);
+        assert!(contour_area(&largest[0]) > 15.0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,168 @@
+use crate::dwc::complex::*;
+use crate::dwc::epicycle::*;
+use crate::dwc::path::*;
+
+pub const STAGE_DURATION: f32 = 2.5; // seconds spent on every term count
+pub const GROW_DURATION: f32 = 1.0; // seconds for the new circles to reach their size
+pub const CURVE_SAMPLE_COUNT: usize = 1000;
+
+// term counts 1, 2, 4, ... and finally `max_count`
+pub fn stage_counts(max_count: usize) -> Vec<usize>
+{
+    let mut counts: Vec<usize> = Vec::new();
+    let mut count: usize = 1;
+
+    while count < max_count
+    {
+        counts.push(count);
+        count *= 2;
+    }
+
+    counts.push(max_count.max(1));
+
+    return counts;
+}
+
+// sweeps the number of terms to show how the series approaches the drawing
+pub struct Convergence
+{
+    counts: Vec<usize>,
+    stage: usize,
+    time: f32 // seconds since the current stage began
+}
+
+// convergence methods
+impl Convergence
+{
+    pub fn new(max_count: usize) -> Convergence
+    {
+        return Convergence { counts: stage_counts(max_count), stage: 0, time: 0.0 };
+    }
+}
+
+impl Convergence
+{
+    // the last stage is held as long as the others, then everything starts over
+    pub fn update(&mut self, elapsed: f32)
+    {
+        self.time += elapsed;
+
+        if self.time >= STAGE_DURATION
+        {
+            self.time = 0.0;
+            self.stage = (self.stage + 1) % self.counts.len();
+        }
+    }
+
+    pub fn term_count(&self) -> usize
+    {
+        return self.counts[self.stage];
+    }
+
+    fn previous_count(&self) -> usize
+    {
+        return if self.stage == 0 { 0 } else { self.counts[self.stage - 1] };
+    }
+
+    // size of the circles added in this stage, from 0 to 1, eased in and out
+    pub fn growth(&self) -> f32
+    {
+        let x: f32 = (self.time / GROW_DURATION).clamp(0.0, 1.0);
+
+        return x * x * (3.0 - 2.0 * x);
+    }
+
+    // the terms of the current stage, the newest ones scaled by their growth
+    pub fn epicycles(&self, coefficients: &[Epicycle]) -> Vec<Epicycle>
+    {
+        let previous: usize = self.previous_count().min(coefficients.len());
+        let count: usize = self.term_count().min(coefficients.len());
+        let growth: f32 = self.growth();
+
+        return coefficients[..count].iter()
+                                    .enumerate()
+                                    .map(|(i, e)| if i < previous { e.clone() } else { Epicycle::new(&e.c0 * growth, e.f) })
+                                    .collect();
+    }
+}
+
+// the whole curve drawn by the epicycles over one period
+pub fn reconstruct(epicycles: &[Epicycle], count: usize) -> Vec<Complex>
+{
+    return (0..count).map(|i| Epicycle::get_combined_position(epicycles, i as f32 / count as f32)).collect();
+}
+
+// mean distance between the path, centred like the epicycles draw it, and the epicycles at the same times
+pub fn reconstruction_error(path: &[Complex], epicycles: &[Epicycle], samples: usize) -> f32
+{
+    if path.is_empty() || samples == 0
+    {
+        return 0.0;
+    }
+
+    let center: Complex = centroid(path);
+    let mut error: f32 = 0.0;
+
+    for i in 0..samples
+    {
+        let index: usize = i * path.len() / samples;
+        let t: f32 = index as f32 / path.len() as f32;
+
+        error += (&path[index] - &center - Epicycle::get_combined_position(epicycles, t)).magnitude();
+    }
+
+    return error / samples as f32;
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn doubling_counts()
+    {
+        assert_eq!(stage_counts(10), Vec::from([1, 2, 4, 8, 10]));
+        assert_eq!(stage_counts(8), Vec::from([1, 2, 4, 8]));
+        assert_eq!(stage_counts(1), Vec::from([1]));
+    }
+
+    #[test]
+    fn new_circles_grow()
+    {
+        let coefficients: Vec<Epicycle> = (1..5).map(|f| Epicycle::new(Complex::new(1.0, 0.0), f as f32)).collect();
+        let mut convergence: Convergence = Convergence::new(4);
+
+        convergence.update(STAGE_DURATION);
+        assert_eq!(convergence.term_count(), 2);
+
+        let epicycles: Vec<Epicycle> = convergence.epicycles(&coefficients);
+        assert_eq!(epicycles.len(), 2);
+        assert_eq!(epicycles[0].c0.magnitude(), 1.0);
+        assert_eq!(epicycles[1].c0.magnitude(), 0.0);
+
+        convergence.update(GROW_DURATION);
+        assert!((convergence.epicycles(&coefficients)[1].c0.magnitude() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn exact_circle()
+    {
+        let path: Vec<Complex> = (0..100).map(|i| euler_formula(i as f32 / 100.0 * 6.2831855) * 3.0 + Complex::new(5.0, 5.0)).collect();
+        let epicycles: Vec<Epicycle> = compute_epicycles(&path, 3).unwrap();
+
+        assert!(reconstruction_error(&path, &epicycles, 50) < 0.01);
+        assert!(reconstruction_error(&path, &Vec::new(), 50) > 2.9);
+    }
+
+    #[test]
+    fn starts_over()
+    {
+        let mut convergence: Convergence = Convergence::new(2);
+
+        convergence.update(STAGE_DURATION);
+        convergence.update(STAGE_DURATION);
+
+        assert_eq!(convergence.term_count(), 1);
+    }
+}
\ No newline at end of file
@@ -3,17 +3,26 @@ extern crate sdl2;
 use crate::dwc::complex::*;
 use crate::dwc::epicycle::*;
 use crate::dwc::common::*;
+use crate::dwc::raster::*;
+use crate::dwc::trail_style::*;
+use crate::dwc::trail::*;
+use crate::dwc::camera::*;
+use crate::dwc::path::*;
+use crate::dwc::font::*;
+use crate::dwc::error::*;
 
 use sdl2::video::Window;
+use sdl2::video::FullscreenType;
 use sdl2::pixels::Color;
 use sdl2::rect::Point;
+use sdl2::rect::Rect;
 use sdl2::render::Canvas;
+use sdl2::render::Texture;
+use sdl2::render::BlendMode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
 
-pub struct Trail
-{
-    max_length: usize,
-    values: Vec<Complex>
-}
+const MAX_LAYER_SIZE: u32 = 4096; // sides of the persisted layer in pixels, bigger curves are drawn directly
 
 pub struct Renderer
 {
@@ -22,133 +31,514 @@ pub struct Renderer
     half_height: f32,
     pub scale: f32, // specify how big the renders are (scale < 1 results in smaller renders)
     pub pixel_size: f32,
+    pub antialiasing: bool, // lines wider than one pixel are always antialiased
+    pub trail_width: f32,
+    pub arm_width: f32,
+    pub circle_width: f32,
+    dpi_scale: f32, // drawable pixels per window pixel, above 1 on high-DPI screens
+    blend_mode: BlendMode,
+    pub camera: Camera,
+    persisted_curve: Option<(Vec<Complex>, Color)>, // finished drawing kept on screen
+    persist_layer: Option<Texture>, // the persisted curve, rendered once for the scale below and moved with the camera
+    persist_scale: [f32; 4], // the scale the layer was rendered at, see layer_scale
+    persist_corner: (f32, f32), // where the top left corner of the layer is, in drawing coordinates
 }
 
-// trail methods
-impl Trail
+// renderer methods
+impl Renderer
 {
-    pub fn new(max_length: usize) -> Trail
+    // with vsync, presenting a frame waits for the screen refresh
+    pub fn new(window: Window, scale: f32, pixel_size: f32, vsync: bool) -> Result<Renderer, Error>
     {
-        return Trail{ max_length: max_length, values: Vec::new() };
+        let builder = if vsync { window.into_canvas().present_vsync() } else { window.into_canvas() };
+        let mut canvas: Canvas<Window> = builder.build().map_err(|e| Error::Sdl(e.to_string()))?;
+
+        // render at the drawable size, which is bigger than the window size on high-DPI screens
+        let size: (u32, u32) = canvas.output_size().map_err(Error::Sdl)?;
+        let width: f32 = size.0 as f32 * 0.5;
+        let height: f32 = size.1 as f32 * 0.5;
+        let dpi_scale: f32 = size.0 as f32 / canvas.window().size().0.max(1) as f32;
+
+        let _ = canvas.set_scale(pixel_size, pixel_size);
+        canvas.set_blend_mode(BlendMode::Add);
+        canvas.present();
+
+        return Ok(Renderer { canvas: canvas, 
+                          half_width: width, 
+                          half_height: height, 
+                          scale: scale, 
+                          pixel_size: pixel_size,
+                          antialiasing: true,
+                          trail_width: 2.0,
+                          arm_width: 1.0,
+                          circle_width: 1.0,
+                          dpi_scale: dpi_scale,
+                          blend_mode: BlendMode::Add,
+                          camera: Camera::new(),
+                          persisted_curve: None,
+                          persist_layer: None,
+                          persist_scale: [0.0; 4],
+                          persist_corner: (0.0, 0.0) });
     }
-}
 
-impl Trail
-{
-    pub fn push(&mut self, value: Complex)
+    pub fn clear(&mut self, color: Color)
     {
-        if self.values.len() == self.max_length
+        self.canvas.set_draw_color(color);
+        self.canvas.clear();
+    }
+
+    // position on the canvas, before rounding to a pixel
+    pub fn to_screen(&self, p: &Complex) -> (f32, f32)
+    {
+        let x: f32 = (p.real - self.camera.center.real) * self.scale * self.camera.zoom;
+        let y: f32 = (p.img - self.camera.center.img) * self.scale * self.camera.zoom;
+
+        return (x + self.half_width / self.pixel_size, -y + self.half_height / self.pixel_size);
+    }
+
+    // drawing coordinates of a position on the canvas
+    pub fn to_world(&self, x: f32, y: f32) -> Complex
+    {
+        let zoom: f32 = self.scale * self.camera.zoom;
+
+        return Complex::new((x - self.half_width / self.pixel_size) / zoom + self.camera.center.real,
+                            -(y - self.half_height / self.pixel_size) / zoom + self.camera.center.img);
+    }
+
+    // choose the scale and centre so the bounds fill the canvas, leaving `margin` (a fraction of the canvas) free on every side
+    pub fn fit_view(&mut self, bounds: &Bounds, margin: f32)
+    {
+        let width: f32 = 2.0 * self.half_width / self.pixel_size * (1.0 - 2.0 * margin);
+        let height: f32 = 2.0 * self.half_height / self.pixel_size * (1.0 - 2.0 * margin);
+
+        if bounds.width() <= 0.0 && bounds.height() <= 0.0
         {
-            self.values.remove(0);
+            return;
         }
 
-        self.values.push(value);
+        self.scale = (width / bounds.width().max(f32::EPSILON)).min(height / bounds.height().max(f32::EPSILON));
+        self.camera.center = bounds.center();
+        self.camera.zoom = 1.0;
     }
 
-    pub fn get_value(&self, idx: usize) -> &Complex
+    // read the canvas size again after the window was resized or moved to another screen, true when it changed
+    pub fn update_size(&mut self) -> bool
     {
-        return &self.values[idx];
+        let view: [f32; 7] = self.view();
+        let dpi_scale: f32 = self.dpi_scale;
+
+        if let Ok((width, height)) = self.canvas.output_size()
+        {
+            self.half_width = width as f32 * 0.5;
+            self.half_height = height as f32 * 0.5;
+            self.dpi_scale = width as f32 / self.canvas.window().size().0.max(1) as f32;
+        }
+
+        return view != self.view() || dpi_scale != self.dpi_scale;
     }
 
-    pub fn get_length(&self) -> usize
+    // resize the window unless it already has this size
+    pub fn set_window_size(&mut self, width: u32, height: u32) -> Result<(), String>
     {
-        return self.values.len();
+        let window: &mut Window = self.canvas.window_mut();
+
+        if window.size() != (width, height)
+        {
+            window.set_size(width, height).map_err(|e| e.to_string())?;
+            self.update_size();
+        }
+
+        return Ok(());
     }
 
-    pub fn get_max_length(&self) -> usize
+    // adding suits dark backgrounds, light ones need alpha blending
+    pub fn set_blend_mode(&mut self, mode: BlendMode)
     {
-        return self.max_length;
+        self.blend_mode = mode;
+        self.canvas.set_blend_mode(mode);
+        self.release_persist_layer();
     }
-}
 
-// renderer methods
-impl Renderer
-{
-    pub fn new(window: Window, scale: f32, pixel_size: f32) -> Renderer
+    // switch between a window and borderless fullscreen on the current screen
+    pub fn toggle_fullscreen(&mut self) -> Result<(), String>
     {
-        let size: (u32, u32) = window.size();
-        let width: f32 = size.0 as f32 * 0.5;
-        let height: f32 = size.1 as f32 * 0.5;
+        let window: &mut Window = self.canvas.window_mut();
+        let state: FullscreenType = match window.fullscreen_state() { FullscreenType::Off => FullscreenType::Desktop, _ => FullscreenType::Off };
 
-        let mut canvas: Canvas<Window> = window.into_canvas().build().unwrap();
-        let _ = canvas.set_scale(pixel_size, pixel_size);
-        canvas.set_blend_mode(sdl2::render::BlendMode::Add);
-        canvas.present();
+        window.set_fullscreen(state)?;
+        self.update_size();
 
-        return Renderer { canvas: canvas, 
-                          half_width: width, 
-                          half_height: height, 
-                          scale: scale, 
-                          pixel_size: pixel_size };
+        return Ok(());
     }
 
-    pub fn clear(&mut self, color: Color)
+    // position on the canvas of a point given in window pixels (mouse events)
+    pub fn window_to_canvas(&self, x: i32, y: i32) -> (f32, f32)
     {
-        self.canvas.set_draw_color(color);
-        self.canvas.clear();
+        return (x as f32 * self.dpi_scale / self.pixel_size, y as f32 * self.dpi_scale / self.pixel_size);
+    }
+
+    // everything that decides where things end up on screen
+    fn view(&self) -> [f32; 7]
+    {
+        return [self.scale, self.camera.zoom, self.camera.center.real, self.camera.center.img,
+                self.half_width, self.half_height, self.pixel_size];
+    }
+
+    // everything but the camera centre, which only moves the persisted layer
+    fn layer_scale(&self) -> [f32; 4]
+    {
+        return [self.scale, self.camera.zoom, self.pixel_size, self.dpi_scale];
     }
 
     pub fn to_screen_point(&self, p: &Complex) -> Point
     {
-        let x: f32 = p.real * self.scale;
-        let y: f32 = p.img * self.scale;
+        let (x, y) = self.to_screen(p);
+
+        return Point::new(x as i32, y as i32);
+    }
+
+    // connected lines through the points, `width` is in window pixels
+    pub fn draw_lines(&mut self, points: &[Complex], width: f32, color: Color)
+    {
+        let width: f32 = width * self.dpi_scale;
+
+        if self.antialiasing || width > 1.0
+        {
+            let screen_points: Vec<(f32, f32)> = points.iter().map(|p| self.to_screen(p)).collect();
+
+            let mut target: CanvasTarget = CanvasTarget::new(&mut self.canvas);
+            draw_polyline(&mut target, &screen_points, width, color);
+            target.flush();
+        }
+        else
+        {
+            let screen_points: Vec<Point> = points.iter().map(|p| self.to_screen_point(p)).collect();
 
-        return Point::new((x + self.half_width / self.pixel_size) as i32,
-                         (-y + self.half_height / self.pixel_size) as i32);
+            self.canvas.set_draw_color(color);
+            let _ = self.canvas.draw_lines(&screen_points[..]);
+        }
     }
 
     pub fn draw_circle(&mut self, position: &Complex, radius: f32, color: Color)
     {
-        let mut points: Vec<Point> = Vec::new();
-        let dtheta: f32 = 1.0 / CIRCLE_EDGE_COUNT as f32;
+        let center: (f32, f32) = self.to_screen(position);
+        let screen_radius: f32 = radius * self.scale * self.camera.zoom;
 
-        for i in 0..CIRCLE_EDGE_COUNT + 1
+        // skip circles that are entirely off screen, common when zoomed in
+        if center.0 + screen_radius < 0.0 || center.1 + screen_radius < 0.0 ||
+           center.0 - screen_radius > 2.0 * self.half_width / self.pixel_size ||
+           center.1 - screen_radius > 2.0 * self.half_height / self.pixel_size
+        {
+            return;
+        }
+
+        // big circles need more edges to look round
+        let edge_count: usize = (screen_radius.sqrt() * 4.0).clamp(CIRCLE_EDGE_COUNT as f32, 512.0) as usize;
+
+        let mut points: Vec<Complex> = Vec::new();
+        let dtheta: f32 = 1.0 / edge_count as f32;
+
+        for i in 0..edge_count + 1
         {
             let mut point: Complex = euler_formula(dtheta * PI_2 * i as f32) * radius;
             point += position;
 
-            points.push(self.to_screen_point(&point));
+            points.push(point);
         }
 
-        let sliced_points: &[Point] = &points[0..points.len()];
-
-        self.canvas.set_draw_color(color);
-        let _ = self.canvas.draw_lines(sliced_points);
+        self.draw_lines(&points, self.circle_width, color);
     }
     
     pub fn draw_trail(&mut self, trail: &Trail, color: Color)
     {
-        let mut points: Vec<Point> = Vec::new();
+        let mut points: Vec<Complex> = Vec::new();
 
         for i in 0..trail.get_length()
         {
-            points.push(self.to_screen_point(trail.get_value(i)));
+            points.push(trail.get_value(i).clone());
         }
 
-        let sliced_points: &[Point] = &points[0..points.len()];
+        self.draw_lines(&points, self.trail_width, color);
+    }
 
-        self.canvas.set_draw_color(color);
-        let _ = self.canvas.draw_lines(sliced_points);
+    // trail with a colour for every segment, `reference` is the original path used to colour by error
+    pub fn draw_styled_trail(&mut self, trail: &Trail, style: &TrailStyle, color: Color, reference: Option<&[Complex]>)
+    {
+        if style.coloring == TrailColoring::Solid && !style.fade
+        {
+            self.draw_trail(trail, color);
+            return;
+        }
+
+        let points: Vec<Complex> = (0..trail.get_length()).map(|i| trail.get_value(i).clone()).collect();
+        let times: Vec<f32> = (0..trail.get_length()).map(|i| trail.get_time(i)).collect();
+        let colors: Vec<Color> = trail_colors(&points, &times, style, color, reference);
+
+        let screen_points: Vec<(f32, f32)> = points.iter().map(|p| self.to_screen(p)).collect();
+        let width: f32 = self.trail_width * self.dpi_scale;
+        let antialiasing: bool = self.antialiasing || width > 1.0;
+        let mut target: CanvasTarget = CanvasTarget::new(&mut self.canvas);
+
+        for i in 0..colors.len()
+        {
+            let segment: &[(f32, f32)] = &screen_points[i..i + 2];
+
+            if antialiasing
+            {
+                draw_polyline(&mut target, segment, width, colors[i]);
+            }
+            else
+            {
+                target.draw_line(segment[0], segment[1], colors[i]);
+            }
+        }
+
+        target.flush();
     }
 
-    pub fn draw_epicycles(&mut self, epicycles: &Vec<Epicycle>, t: f32, color: Color)
+    // one colour per circle, the arms are drawn in `arm_color`
+    pub fn draw_epicycles(&mut self, epicycles: &Vec<Epicycle>, t: f32, circle_colors: &[Color], arm_color: Color)
     {
         let mut tip: Complex = Complex::new(0.0, 0.0);
-        let mut points: Vec<Point> = Vec::new();
-        points.push(self.to_screen_point(&Complex::new(0.0, 0.0)));
+        let mut points: Vec<Complex> = Vec::new();
+        points.push(Complex::new(0.0, 0.0));
 
         for i in 0..epicycles.len()
         {
-            self.draw_circle(&tip, epicycles[i].c0.magnitude(), Color::RGBA(color.r, color.g, color.b, 70));
+            self.draw_circle(&tip, epicycles[i].c0.magnitude(), circle_colors[i]);
 
             tip += epicycles[i].get_position(t);
-            points.push(self.to_screen_point(&tip));
+            points.push(tip.clone());
         }
 
-        let sliced_points: &[Point] = &points[0..points.len()];
+        self.draw_lines(&points, self.arm_width, arm_color);
+    }
+
+    // keep a whole curve on screen, it is only rendered again when the view changes
+    pub fn persist_curve(&mut self, points: Vec<Complex>, color: Color)
+    {
+        self.persisted_curve = Some((points, color));
+        self.release_persist_layer();
+    }
+
+    pub fn clear_persisted(&mut self)
+    {
+        self.persisted_curve = None;
+        self.release_persist_layer();
+    }
+
+    pub fn has_persisted(&self) -> bool
+    {
+        return self.persisted_curve.is_some();
+    }
+
+    fn release_persist_layer(&mut self)
+    {
+        if let Some(texture) = self.persist_layer.take()
+        {
+            // the canvas is still alive, so the texture can be destroyed safely
+            unsafe { texture.destroy(); }
+        }
+    }
+
+    // the layer only covers the curve, so panning and following the tip just move it
+    fn render_persist_layer(&mut self) -> Result<(), String>
+    {
+        let (points, color) = match &self.persisted_curve { Some(curve) => curve.clone(), None => return Ok(()) };
+        let bounds: Bounds = Bounds::from_points(&points).ok_or_else(|| String::from("empty curve"))?;
+
+        let line_width: f32 = self.trail_width * self.dpi_scale;
+        let antialiasing: bool = self.antialiasing || line_width > 1.0;
+        let blend_mode: BlendMode = self.blend_mode;
+
+        // canvas units around the curve, for the width of the line
+        let margin: f32 = line_width + 2.0;
+        let units: f32 = self.scale * self.camera.zoom;
+        let width: u32 = ((bounds.width() * units + 2.0 * margin) * self.pixel_size).ceil() as u32;
+        let height: u32 = ((bounds.height() * units + 2.0 * margin) * self.pixel_size).ceil() as u32;
+
+        if width > MAX_LAYER_SIZE || height > MAX_LAYER_SIZE
+        {
+            return Err(String::from("the curve is too big for a layer at this zoom"));
+        }
+
+        let mut screen_points: Vec<(f32, f32)> = points.iter().map(|p| ((p.real - bounds.min.real) * units + margin, (bounds.max.img - p.img) * units + margin)).collect();
+
+        if let Some(first) = screen_points.first().cloned()
+        {
+            screen_points.push(first);
+        }
+
+        let mut texture: Texture = self.canvas.texture_creator()
+                                              .create_texture_target(PixelFormatEnum::RGBA8888, width.max(1), height.max(1))
+                                              .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(blend_mode);
+
+        self.canvas.with_texture_canvas(&mut texture, |canvas: &mut Canvas<Window>|
+        {
+            // the layer is added or blended onto the frame, so black or transparent is see-through
+            canvas.set_draw_color(Color::RGBA(0, 0, 0, if blend_mode == BlendMode::Add { 255 } else { 0 }));
+            canvas.clear();
+
+            let mut target: CanvasTarget = CanvasTarget::new(canvas);
+
+            for segment in screen_points.windows(2)
+            {
+                if antialiasing
+                {
+                    draw_polyline(&mut target, segment, line_width, color);
+                }
+                else
+                {
+                    target.draw_line(segment[0], segment[1], color);
+                }
+            }
+
+            target.flush();
+        }).map_err(|e| e.to_string())?;
+
+        self.persist_layer = Some(texture);
+        self.persist_scale = self.layer_scale();
+        self.persist_corner = (bounds.min.real - margin / units, bounds.max.img + margin / units);
+
+        return Ok(());
+    }
+
+    pub fn draw_persisted(&mut self)
+    {
+        if self.persisted_curve.is_none()
+        {
+            return;
+        }
+
+        if self.persist_layer.is_none() || self.persist_scale != self.layer_scale()
+        {
+            self.release_persist_layer();
+
+            if self.render_persist_layer().is_err()
+            {
+                // no render target support or a huge zoom, draw the curve directly every frame instead
+                let (mut points, color) = self.persisted_curve.clone().unwrap();
+                points.push(points[0].clone());
+                self.draw_lines(&points, self.trail_width, color);
+                return;
+            }
+        }
+
+        // the layer is in output pixels, copy it without the canvas scale
+        let (x, y) = self.to_screen(&Complex::new(self.persist_corner.0, self.persist_corner.1));
+        let scale: (f32, f32) = self.canvas.scale();
+        let _ = self.canvas.set_scale(1.0, 1.0);
+
+        if let Some(texture) = &self.persist_layer
+        {
+            let query = texture.query();
+            let destination: Rect = Rect::new((x * scale.0).round() as i32, (y * scale.1).round() as i32, query.width, query.height);
+            let _ = self.canvas.copy(texture, None, destination);
+        }
+
+        let _ = self.canvas.set_scale(scale.0, scale.1);
+    }
+
+    // x, y, width and height of the timeline bar along the bottom of the canvas
+    fn timeline_rect(&self) -> (f32, f32, f32, f32)
+    {
+        let unit: f32 = self.dpi_scale / self.pixel_size;
+        let (width, height) = self.overlay_size();
+
+        return (20.0 * unit, height - 30.0 * unit, width - 40.0 * unit, 6.0 * unit);
+    }
+
+    // whether a canvas position is on the timeline, with some room above and below to grab it
+    pub fn timeline_hit(&self, x: f32, y: f32) -> bool
+    {
+        let (left, top, width, height) = self.timeline_rect();
+
+        return x >= left && x <= left + width && y >= top - height && y <= top + 2.0 * height;
+    }
+
+    // progress along the timeline under a canvas position
+    pub fn timeline_progress(&self, x: f32) -> f32
+    {
+        let (left, _, width, _) = self.timeline_rect();
+
+        return ((x - left) / width.max(1.0)).clamp(0.0, 1.0);
+    }
+
+    pub fn draw_timeline(&mut self, progress: f32, color: Color)
+    {
+        let (left, top, width, height) = self.timeline_rect();
+
+        self.canvas.set_draw_color(Color::RGBA(color.r, color.g, color.b, 50));
+        let _ = self.canvas.fill_rect(Rect::new(left as i32, top as i32, width.max(1.0) as u32, height.max(1.0) as u32));
+
+        self.canvas.set_draw_color(Color::RGBA(color.r, color.g, color.b, 200));
+        let _ = self.canvas.fill_rect(Rect::new(left as i32, top as i32, (width * progress).max(1.0) as u32, height.max(1.0) as u32));
+    }
+
+    // text with the built-in font, `size` is the number of window pixels per font pixel
+    pub fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Color)
+    {
+        let unit: f32 = size * self.dpi_scale / self.pixel_size;
+        let side: u32 = unit.ceil().max(1.0) as u32;
+
+        let rects: Vec<Rect> = text_pixels(text).iter()
+                                                .map(|(px, py)| Rect::new((x + *px as f32 * unit) as i32, (y + *py as f32 * unit) as i32, side, side))
+                                                .collect();
 
         self.canvas.set_draw_color(color);
-        let _ = self.canvas.draw_lines(sliced_points);
+        let _ = self.canvas.fill_rects(&rects);
+    }
+
+    // a line of text at the bottom left, just above the timeline
+    pub fn draw_message(&mut self, text: &str, color: Color)
+    {
+        let (x, y, _, _) = self.timeline_rect();
+        let unit: f32 = self.overlay_unit();
+        let size: f32 = 2.0;
+
+        self.draw_text(text, x, y - (text_size(text).1 as f32 * size + 10.0) * unit, size, color);
+    }
+
+    // a shape scaled to -1..1 (see thumbnail_points) drawn in a square of `size` at x, y
+    pub fn draw_thumbnail(&mut self, points: &[Complex], x: f32, y: f32, size: f32, color: Color)
+    {
+        let half: f32 = size * 0.5;
+        let screen_points: Vec<(f32, f32)> = points.iter().map(|p| (x + half + p.real * half, y + half - p.img * half)).collect();
+
+        let mut target: CanvasTarget = CanvasTarget::new(&mut self.canvas);
+        draw_polyline(&mut target, &screen_points, self.dpi_scale, color);
+        target.flush();
+    }
+
+    // size of the canvas in the coordinates of draw_text
+    pub fn overlay_size(&self) -> (f32, f32)
+    {
+        return (2.0 * self.half_width / self.pixel_size, 2.0 * self.half_height / self.pixel_size);
+    }
+
+    // size of one window pixel on the canvas, to lay out overlays
+    pub fn overlay_unit(&self) -> f32
+    {
+        return self.dpi_scale / self.pixel_size;
+    }
+
+    // write what has been drawn since clear to a bmp, must come before display
+    pub fn save_screenshot(&mut self, file: &str) -> Result<(), Error>
+    {
+        // read every drawable pixel, whatever the pixel size
+        let (scale_x, scale_y) = self.canvas.scale();
+        let _ = self.canvas.set_scale(1.0, 1.0);
+        let pixels: Result<Vec<u8>, String> = self.canvas.read_pixels(None, PixelFormatEnum::RGB24);
+        let _ = self.canvas.set_scale(scale_x, scale_y);
+
+        let mut pixels: Vec<u8> = pixels.map_err(Error::Sdl)?;
+        let (width, height) = self.canvas.output_size().map_err(Error::Sdl)?;
+
+        let surface: Surface = Surface::from_data(&mut pixels, width, height, width * 3, PixelFormatEnum::RGB24).map_err(Error::Sdl)?;
+        surface.save_bmp(file).map_err(|e| Error::Sdl(format!("{}: {}", file, e)))?;
+
+        return Ok(());
     }
 
     pub fn display(&mut self)
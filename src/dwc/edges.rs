@@ -0,0 +1,188 @@
+use crate::dwc::image_loader::*;
+
+// default hysteresis thresholds, as fractions of the strongest gradient
+pub const CANNY_LOW: f32 = 0.1;
+pub const CANNY_HIGH: f32 = 0.25;
+
+// gradient of an image, computed with the sobel operator
+pub struct Gradient
+{
+    pub magnitude: GrayImage,
+    pub direction: Vec<f32>, // angle of the gradient in radians
+}
+
+fn clamped(image: &GrayImage, x: i64, y: i64) -> f32
+{
+    let cx: usize = x.clamp(0, image.width as i64 - 1) as usize;
+    let cy: usize = y.clamp(0, image.height as i64 - 1) as usize;
+
+    return image.get(cx, cy);
+}
+
+// 5x5 gaussian blur to keep noise from showing up as edges
+pub fn blur(image: &GrayImage) -> GrayImage
+{
+    const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+
+    let mut horizontal: GrayImage = GrayImage::new(image.width, image.height);
+    let mut result: GrayImage = GrayImage::new(image.width, image.height);
+
+    for y in 0..image.height
+    {
+        for x in 0..image.width
+        {
+            let value: f32 = (0..5).map(|k| KERNEL[k] * clamped(image, x as i64 + k as i64 - 2, y as i64)).sum();
+            horizontal.set(x, y, value);
+        }
+    }
+
+    for y in 0..image.height
+    {
+        for x in 0..image.width
+        {
+            let value: f32 = (0..5).map(|k| KERNEL[k] * clamped(&horizontal, x as i64, y as i64 + k as i64 - 2)).sum();
+            result.set(x, y, value);
+        }
+    }
+
+    return result;
+}
+
+pub fn sobel(image: &GrayImage) -> Gradient
+{
+    let mut magnitude: GrayImage = GrayImage::new(image.width, image.height);
+    let mut direction: Vec<f32> = vec![0.0; image.width * image.height];
+
+    for y in 0..image.height as i64
+    {
+        for x in 0..image.width as i64
+        {
+            let p = |dx: i64, dy: i64| -> f32 { clamped(image, x + dx, y + dy) };
+
+            let gx: f32 = (p(1, -1) + 2.0 * p(1, 0) + p(1, 1)) - (p(-1, -1) + 2.0 * p(-1, 0) + p(-1, 1));
+            let gy: f32 = (p(-1, 1) + 2.0 * p(0, 1) + p(1, 1)) - (p(-1, -1) + 2.0 * p(0, -1) + p(1, -1));
+
+            magnitude.set(x as usize, y as usize, (gx * gx + gy * gy).sqrt());
+            direction[y as usize * image.width + x as usize] = gy.atan2(gx);
+        }
+    }
+
+    return Gradient { magnitude: magnitude, direction: direction };
+}
+
+// canny edge detector, returns the (x, y) of every edge pixel
+// `low` and `high` are the hysteresis thresholds as fractions of the strongest gradient
+pub fn canny(image: &GrayImage, low: f32, high: f32) -> Vec<(usize, usize)>
+{
+    let gradient: Gradient = sobel(&blur(image));
+    let (width, height) = (image.width, image.height);
+    let strongest: f32 = gradient.magnitude.pixels.iter().cloned().fold(0.0, f32::max);
+
+    if strongest <= 0.0
+    {
+        return Vec::new();
+    }
+
+    // non-maximum suppression keeps the ridge of each edge, one pixel wide
+    let mut thin: GrayImage = GrayImage::new(width, height);
+
+    for y in 1..height.saturating_sub(1)
+    {
+        for x in 1..width.saturating_sub(1)
+        {
+            let value: f32 = gradient.magnitude.get(x, y);
+            let angle: f32 = gradient.direction[y * width + x].to_degrees().rem_euclid(180.0);
+
+            let (dx, dy): (i64, i64) = if !(22.5..157.5).contains(&angle) { (1, 0) }
+                                       else if angle < 67.5 { (1, 1) }
+                                       else if angle < 112.5 { (0, 1) }
+                                       else { (-1, 1) };
+
+            let before: f32 = gradient.magnitude.get((x as i64 - dx) as usize, (y as i64 - dy) as usize);
+            let after: f32 = gradient.magnitude.get((x as i64 + dx) as usize, (y as i64 + dy) as usize);
+
+            if value >= before && value >= after
+            {
+                thin.set(x, y, value / strongest);
+            }
+        }
+    }
+
+    // hysteresis, weak edges survive only when connected to a strong one
+    let mut edge: Vec<bool> = vec![false; width * height];
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            if thin.get(x, y) >= high
+            {
+                edge[y * width + x] = true;
+                stack.push((x, y));
+            }
+        }
+    }
+
+    while let Some((x, y)) = stack.pop()
+    {
+        for ny in y.saturating_sub(1)..(y + 2).min(height)
+        {
+            for nx in x.saturating_sub(1)..(x + 2).min(width)
+            {
+                if !edge[ny * width + nx] && thin.get(nx, ny) >= low
+                {
+                    edge[ny * width + nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+    }
+
+    let mut points: Vec<(usize, usize)> = Vec::new();
+
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            if edge[y * width + x]
+            {
+                points.push((x, y));
+            }
+        }
+    }
+
+    return points;
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn vertical_edge()
+    {
+        // left half black, right half white
+        let mut image: GrayImage = GrayImage::new(16, 16);
+
+        for y in 0..16
+        {
+            for x in 8..16
+            {
+                image.set(x, y, 1.0);
+            }
+        }
+
+        let points: Vec<(usize, usize)> = canny(&image, 0.1, 0.3);
+
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|(x, _)| *x >= 6 && *x <= 9));
+    }
+
+    #[test]
+    fn flat_image_has_no_edges()
+    {
+        assert!(canny(&GrayImage::new(8, 8), 0.1, 0.3).is_empty());
+    }
+}
\ No newline at end of file
@@ -1,5 +1,7 @@
 use crate::dwc::common::*;
 use crate::dwc::complex::*;
+use crate::dwc::path::*;
+use crate::dwc::error::*;
 
 #[derive(Clone)]
 pub struct Epicycle
@@ -16,7 +18,7 @@ impl Epicycle
     }
 
     // get the value of multiple epicycles combined
-    pub fn get_combined_position(epicycles: &Vec<Epicycle>, t: f32) -> Complex
+    pub fn get_combined_position(epicycles: &[Epicycle], t: f32) -> Complex
     {
         let mut vec: Complex = Complex::new(0.0, 0.0);
 
@@ -66,8 +68,19 @@ fn compute_cn(shape: &Vec<Complex>, f: f32, dt: f32) -> Complex
     return cn;
 }
 
-pub fn compute_epicycles(shape: &Vec<Complex>, n: u8) -> Vec<Epicycle>
+// the errors name the shape "path", callers that know where it came from rename it with Error::with_source
+pub fn compute_epicycles(shape: &Vec<Complex>, n: usize) -> Result<Vec<Epicycle>, Error>
 {
+    if shape.is_empty()
+    {
+        return Err(Error::EmptyPath(String::from("path")));
+    }
+
+    if is_degenerate(shape)
+    {
+        return Err(Error::Degenerate(String::from("path")));
+    }
+
     let mut epicycles: Vec<Epicycle> = Vec::new();
     let dt = 1.0 / shape.len() as f32;
 
@@ -84,7 +97,27 @@ pub fn compute_epicycles(shape: &Vec<Complex>, n: u8) -> Vec<Epicycle>
         epicycles.push(epicycle_n);
     }
 
-    return epicycles;
+    return Ok(epicycles);
+}
+
+// which terms come first in the chain, and so are kept when only part of it is shown
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum TermOrder
+{
+    Magnitude, // biggest circles first
+    Frequency, // slowest first, +f before -f as compute_epicycles returns them
+}
+
+pub fn sort_epicycles(epicycles: &mut [Epicycle], order: TermOrder)
+{
+    match order
+    {
+        TermOrder::Magnitude => epicycles.sort_by(|a, b| b.c0.magnitude().total_cmp(&a.c0.magnitude())),
+        TermOrder::Frequency => epicycles.sort_by(|a, b| a.f.abs().total_cmp(&b.f.abs()).then(b.f.total_cmp(&a.f))),
+    }
 }
 
 #[cfg(test)]
@@ -128,4 +161,29 @@ mod test
         assert!(complex_eq(p2, Complex::new(-1.0, 1.0)));
         assert!(complex_eq(p3, Complex::new(-hyp, 0.0)));
     }
+
+    #[test]
+    fn nothing_to_draw()
+    {
+        assert!(matches!(compute_epicycles(&Vec::new(), 10), Err(Error::EmptyPath(_))));
+        assert!(matches!(compute_epicycles(&vec![Complex::new(1.0, 1.0); 4], 10), Err(Error::Degenerate(_))));
+    }
+
+    #[test]
+    fn sort_terms()
+    {
+        let mut es: Vec<Epicycle> = Vec::from
+        ([
+            Epicycle::new(Complex::new(1.0, 0.0), -2.0),
+            Epicycle::new(Complex::new(3.0, 0.0), 2.0),
+            Epicycle::new(Complex::new(2.0, 0.0), -1.0),
+            Epicycle::new(Complex::new(0.5, 0.0), 1.0),
+        ]);
+
+        sort_epicycles(&mut es, TermOrder::Magnitude);
+        assert_eq!(es.iter().map(|e| e.f).collect::<Vec<f32>>(), Vec::from([2.0, -1.0, -2.0, 1.0]));
+
+        sort_epicycles(&mut es, TermOrder::Frequency);
+        assert_eq!(es.iter().map(|e| e.f).collect::<Vec<f32>>(), Vec::from([1.0, -1.0, 2.0, -2.0]));
+    }
 }
\ No newline at end of file
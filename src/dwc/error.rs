@@ -0,0 +1,91 @@
+use std::fmt;
+
+// everything that can go wrong while loading a shape or starting the window
+#[derive(Debug)]
+pub enum Error
+{
+    Io { file: String, source: std::io::Error },
+    Parse { file: String, line: usize, column: usize, message: String }, // line and column start at 1
+    Format { file: String, message: String }, // invalid content with no position to report
+    EmptyPath(String), // what the path was loaded from
+    Degenerate(String), // every point at the same place, nothing to draw
+    Sdl(String),
+    Usage(String), // invalid command line or config setting
+}
+
+// error methods
+impl Error
+{
+    pub fn io(file: &str, source: std::io::Error) -> Error
+    {
+        return Error::Io { file: String::from(file), source: source };
+    }
+
+    pub fn parse(file: &str, line: usize, column: usize, message: &str) -> Error
+    {
+        return Error::Parse { file: String::from(file), line: line, column: column, message: String::from(message) };
+    }
+
+    pub fn format(file: &str, message: &str) -> Error
+    {
+        return Error::Format { file: String::from(file), message: String::from(message) };
+    }
+}
+
+impl Error
+{
+    // name what an EmptyPath or Degenerate error was raised for, where that was not known
+    pub fn with_source(self, name: &str) -> Error
+    {
+        return match self
+        {
+            Error::EmptyPath(_) => Error::EmptyPath(String::from(name)),
+            Error::Degenerate(_) => Error::Degenerate(String::from(name)),
+            _ => self,
+        };
+    }
+}
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        return match self
+        {
+            Error::Io { file, source } => write!(f, "{}: {}", file, source),
+            Error::Parse { file, line, column, message } => write!(f, "{}:{}:{}: {}", file, line, column, message),
+            Error::Format { file, message } => write!(f, "{}: {}", file, message),
+            Error::EmptyPath(file) => write!(f, "{}: the path has no points", file),
+            Error::Degenerate(file) => write!(f, "{}: every point of the path is at the same place", file),
+            Error::Sdl(message) => write!(f, "sdl: {}", message),
+            Error::Usage(message) => write!(f, "{}", message),
+        };
+    }
+}
+
+impl std::error::Error for Error
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        return match self
+        {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn messages()
+    {
+        assert_eq!(Error::parse("a.txt", 3, 7, "invalid number 'x'").to_string(), "a.txt:3:7: invalid number 'x'");
+        assert_eq!(Error::EmptyPath(String::from("b.svg")).to_string(), "b.svg: the path has no points");
+        assert_eq!(Error::Degenerate(String::from("path")).with_source("drawing").to_string(), "drawing: every point of the path is at the same place");
+        assert_eq!(Error::Sdl(String::from("no video")).with_source("drawing").to_string(), "sdl: no video");
+    }
+}
\ No newline at end of file
@@ -1,47 +1,494 @@
 use std::fs;
+use crate::dwc::common::*;
 use crate::dwc::complex::*;
+use crate::dwc::path::*;
+use crate::dwc::svg_loader::*;
+use crate::dwc::contour::*;
+use crate::dwc::tour::*;
+use crate::dwc::epicycle::*;
+use crate::dwc::error::*;
+use crate::dwc::binary_path::*;
+use crate::dwc::convergence::*;
 
-fn extract_words(input: &String) -> Vec<String>
+// words of a line with the column (from 1) they start at, separated by whitespace or commas
+fn extract_words(line: &str) -> Vec<(usize, &str)>
 {
-    let mut words: Vec<String> = Vec::new();
-    let words_str: Vec<&str> = input.split(' ').collect();
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<(usize, usize)> = None; // byte offset and column of the current word
 
-    for i in 0..words_str.len()
+    for (column, (offset, c)) in line.char_indices().enumerate()
     {
-        words.push(words_str[i].to_string());
+        let separator: bool = c.is_whitespace() || c == ',';
+
+        match (start, separator)
+        {
+            (None, false) => start = Some((offset, column + 1)),
+            (Some((word_start, word_column)), true) =>
+            {
+                words.push((word_column, &line[word_start..offset]));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    if let Some((word_start, word_column)) = start
+    {
+        words.push((word_column, &line[word_start..]));
     }
 
     return words;
 }
 
-fn extract_lines(input: &String) -> Vec<String>
+// points read from a text file, with the metadata of its header
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct TextPath
 {
-    let mut words: Vec<String> = Vec::new();
+    pub name: Option<String>,
+    pub closed: bool, // open paths are traced forward then back, so the loop has no jump
+    pub units: Option<String>,
+    pub strokes: Vec<Vec<Complex>> // separated by blank lines
+}
 
-    for i in input.lines()
+impl TextPath
+{
+    // the strokes joined into one closed shape
+    pub fn to_shape(&self) -> Vec<Complex>
     {
-            words.push(i.to_string());
+        let line: Vec<Complex> = join_strokes(&self.strokes);
+
+        if self.closed || line.len() < 3
+        {
+            return line;
+        }
+
+        let mut shape: Vec<Complex> = line.clone();
+        shape.extend(line[1..line.len() - 1].iter().rev().cloned());
+
+        return shape;
     }
+}
 
-    return words;
+const X_COLUMNS: [&str; 4] = ["x", "real", "re", "u"];
+const Y_COLUMNS: [&str; 5] = ["y", "img", "imag", "im", "v"];
+
+// one "real imaginary" point per line, separated by whitespace or commas, `file` is only used in errors
+// '#' starts a comment, blank lines split strokes, "key: value" lines before the points set name, closed and units,
+// and a first row of column names (csv header) picks the x and y columns
+pub fn parse_text_path(contents: &str, file: &str) -> Result<TextPath, Error>
+{
+    let mut path: TextPath = TextPath { name: None, closed: true, units: None, strokes: Vec::new() };
+    let mut stroke: Vec<Complex> = Vec::new();
+    let mut columns: (usize, usize) = (0, 1);
+    let mut in_header: bool = true;
+
+    for (index, line) in contents.lines().enumerate()
+    {
+        let content: &str = line.split('#').next().unwrap_or("");
+        let words: Vec<(usize, &str)> = extract_words(content);
+
+        if words.is_empty()
+        {
+            // comment lines do not end a stroke
+            if line.trim().is_empty() && !stroke.is_empty()
+            {
+                path.strokes.push(std::mem::take(&mut stroke));
+            }
+
+            continue;
+        }
+
+        if in_header
+        {
+            if let Some((key, value)) = content.split_once(':')
+            {
+                // the value starts after the colon and the spaces following it
+                let value_column: usize = key.chars().count() + value.chars().take_while(|c| c.is_whitespace()).count() + 2;
+                let value: String = String::from(value.trim());
+
+                match key.trim().to_ascii_lowercase().as_str()
+                {
+                    "name" => path.name = Some(value),
+                    "units" => path.units = Some(value),
+                    "closed" | "open" =>
+                    {
+                        let yes: bool = match value.to_ascii_lowercase().as_str()
+                        {
+                            "true" | "yes" | "1" => true,
+                            "false" | "no" | "0" => false,
+                            _ => return Err(Error::parse(file, index + 1, value_column, &format!("expected true or false, found '{}'", value))),
+                        };
+
+                        path.closed = if key.trim().eq_ignore_ascii_case("closed") { yes } else { !yes };
+                    },
+                    _ => return Err(Error::parse(file, index + 1, words[0].0, &format!("unknown header key '{}'", key.trim()))),
+                }
+
+                continue;
+            }
+
+            // column names instead of numbers on the first row
+            if words[0].1.parse::<f32>().is_err()
+            {
+                let find = |names: &[&str]| words.iter().position(|(_, w)| names.iter().any(|n| w.trim_matches('"').eq_ignore_ascii_case(n)));
+
+                columns = match (find(&X_COLUMNS), find(&Y_COLUMNS))
+                {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => return Err(Error::parse(file, index + 1, words[0].0, "header row without x and y columns")),
+                };
+
+                in_header = false;
+                continue;
+            }
+
+            in_header = false;
+        }
+
+        let last: usize = columns.0.max(columns.1);
+
+        if words.len() <= last
+        {
+            let message: String = format!("expected {} values", last + 1);
+            return Err(Error::parse(file, index + 1, line.chars().count() + 1, &message));
+        }
+
+        let mut values: [f32; 2] = [0.0; 2];
+
+        for (value, column) in values.iter_mut().zip([columns.0, columns.1])
+        {
+            let (position, word) = words[column];
+            *value = word.parse::<f32>().map_err(|_| Error::parse(file, index + 1, position, &format!("invalid number '{}'", word)))?;
+        }
+
+        stroke.push(Complex { real: values[0], img: values[1] });
+    }
+
+    if !stroke.is_empty()
+    {
+        path.strokes.push(stroke);
+    }
+
+    return Ok(path);
 }
 
-pub fn load_from_txt(path: &str) -> Vec<Complex>
+// the shape described by a text file
+pub fn parse_txt(contents: &str, file: &str) -> Result<Vec<Complex>, Error>
 {
-    let contents: String = fs::read_to_string(path).unwrap();
-    let mut points: Vec<Complex> = Vec::new();
+    return Ok(parse_text_path(contents, file)?.to_shape());
+}
+
+pub fn load_from_txt(path: &str) -> Result<Vec<Complex>, Error>
+{
+    let contents: String = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
 
-    let lines: Vec<String> = extract_lines(&contents);
+    return parse_txt(&contents, path);
+}
 
-    for line in lines
+// write points in the format read by load_from_txt
+pub fn save_to_txt(path: &str, points: &[Complex]) -> Result<(), Error>
+{
+    let mut contents: String = String::new();
+
+    for p in points
+    {
+        contents += &format!("{} {}\n", p.real, p.img);
+    }
+
+    return fs::write(path, contents).map_err(|e| Error::io(path, e));
+}
+
+// strokes separated by blank lines, as parse_text_path reads them
+pub fn save_strokes_to_txt(path: &str, strokes: &[Vec<Complex>]) -> Result<(), Error>
+{
+    let mut contents: String = String::new();
+
+    for (i, stroke) in strokes.iter().enumerate()
+    {
+        if i > 0
+        {
+            contents += "\n";
+        }
+
+        for p in stroke
+        {
+            contents += &format!("{} {}\n", p.real, p.img);
+        }
+    }
+
+    return fs::write(path, contents).map_err(|e| Error::io(path, e));
+}
+
+// first line of a coefficient file, which tells it apart from a text path
+pub const COEFFICIENTS_HEADER: &str = "# epicycles: frequency real imaginary";
+
+// one "frequency real imaginary" line per epicycle, after the header
+pub fn format_coefficients(epicycles: &[Epicycle]) -> String
+{
+    let mut contents: String = format!("{}\n", COEFFICIENTS_HEADER);
+
+    for e in epicycles
+    {
+        contents += &format!("{} {} {}\n", e.f, e.c0.real, e.c0.img);
+    }
+
+    return contents;
+}
+
+// how bitmaps are turned into a shape
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum ImageTrace
+{
+    Contours(ContourSelection), // outlines of the dark regions
+    SingleLine, // every edge joined into one continuous line
+}
+
+// "frequency real imaginary" lines as written by format_coefficients, # starts a comment
+pub fn parse_coefficients(contents: &str, file: &str) -> Result<Vec<Epicycle>, Error>
+{
+    let mut epicycles: Vec<Epicycle> = Vec::new();
+
+    for (index, line) in contents.lines().enumerate()
     {
-        let words: Vec<String> = extract_words(&line);
+        let words: Vec<(usize, &str)> = extract_words(line.split('#').next().unwrap_or(""));
+
+        if words.is_empty()
+        {
+            continue;
+        }
 
-        let real: f32 = words[0].parse::<f32>().unwrap();
-        let img: f32 = words[1].parse::<f32>().unwrap();
+        if words.len() != 3
+        {
+            return Err(Error::parse(file, index + 1, words[0].0, "expected frequency, real and imaginary parts"));
+        }
 
-        points.push(Complex { real: real, img: img });
+        let mut values: [f32; 3] = [0.0; 3];
+
+        for (value, (position, word)) in values.iter_mut().zip(words)
+        {
+            *value = word.parse::<f32>().map_err(|_| Error::parse(file, index + 1, position, &format!("invalid number '{}'", word)))?;
+        }
+
+        epicycles.push(Epicycle::new(Complex::new(values[1], values[2]), values[0]));
     }
 
-    return points;
+    return Ok(epicycles);
+}
+
+pub fn load_coefficients(path: &str) -> Result<Vec<Epicycle>, Error>
+{
+    let contents: String = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+
+    return parse_coefficients(&contents, path);
+}
+
+// what a file holds
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum PathFormat
+{
+    Text,
+    Binary,
+    Svg,
+    Image, // bmp or netpbm
+    Coefficients,
+}
+
+// guess the format from the start of a file
+pub fn format_from_bytes(bytes: &[u8]) -> PathFormat
+{
+    let text: String = String::from_utf8_lossy(bytes).trim_start().to_ascii_lowercase();
+
+    if bytes.starts_with(&BINARY_MAGIC)
+    {
+        return PathFormat::Binary;
+    }
+
+    if bytes.starts_with(b"BM") || (bytes.len() >= 2 && bytes[0] == b'P' && b"2356".contains(&bytes[1]))
+    {
+        return PathFormat::Image;
+    }
+
+    if text.starts_with("<?xml") || text.starts_with("<svg")
+    {
+        return PathFormat::Svg;
+    }
+
+    if text.starts_with(COEFFICIENTS_HEADER)
+    {
+        return PathFormat::Coefficients;
+    }
+
+    return PathFormat::Text;
+}
+
+// by extension, or by the first bytes for files without a known one
+pub fn detect_format(path: &str) -> Result<PathFormat, Error>
+{
+    let extensions: [(&str, PathFormat); 9] = [("txt", PathFormat::Text), ("csv", PathFormat::Text), ("dwcp", PathFormat::Binary),
+                                               ("svg", PathFormat::Svg), ("bmp", PathFormat::Image), ("pgm", PathFormat::Image),
+                                               ("ppm", PathFormat::Image), ("pnm", PathFormat::Image), ("coef", PathFormat::Coefficients)];
+
+    if let Some((_, format)) = extensions.iter().find(|(extension, _)| has_extension(path, extension))
+    {
+        return Ok(*format);
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    let file: fs::File = fs::File::open(path).map_err(|e| Error::io(path, e))?;
+    std::io::Read::read_to_end(&mut std::io::Read::take(file, 64), &mut bytes).map_err(|e| Error::io(path, e))?;
+
+    return Ok(format_from_bytes(&bytes));
+}
+
+pub fn has_extension(path: &str, extension: &str) -> bool
+{
+    return std::path::Path::new(path).extension()
+                                     .map(|e| e.eq_ignore_ascii_case(extension))
+                                     .unwrap_or(false);
+}
+
+// load any supported file as a single shape ready for compute_epicycles
+pub fn load_shape(path: &str) -> Result<Vec<Complex>, Error>
+{
+    return load_shape_traced(path, ImageTrace::Contours(ContourSelection::Largest));
+}
+
+pub fn load_shape_traced(path: &str, trace: ImageTrace) -> Result<Vec<Complex>, Error>
+{
+    let shape: Vec<Complex> = match detect_format(path)?
+    {
+        PathFormat::Svg => resample(&join_strokes(&load_from_svg(path)?), SHAPE_SAMPLE_COUNT),
+        PathFormat::Binary => join_strokes(&load_from_binary(path)?),
+        PathFormat::Image =>
+        {
+            let line: Vec<Complex> = match trace
+            {
+                ImageTrace::Contours(selection) => join_strokes(&load_from_image(path, selection)?),
+                ImageTrace::SingleLine => load_single_line(path)?,
+            };
+
+            resample(&line, line.len().max(SHAPE_SAMPLE_COUNT))
+        },
+        // the curve the series draws, its coefficients come back when the path is analysed again
+        PathFormat::Coefficients => reconstruct(&load_coefficients(path)?, SHAPE_SAMPLE_COUNT),
+        PathFormat::Text => load_from_txt(path)?,
+    };
+
+    if shape.is_empty()
+    {
+        return Err(Error::EmptyPath(String::from(path)));
+    }
+
+    if is_degenerate(&shape)
+    {
+        return Err(Error::Degenerate(String::from(path)));
+    }
+
+    return Ok(shape);
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn points()
+    {
+        let points: Vec<Complex> = parse_txt("1 2\n\n-3.5  4e1\n", "a.txt").unwrap();
+
+        assert_eq!(points, Vec::from([Complex::new(1.0, 2.0), Complex::new(-3.5, 40.0)]));
+    }
+
+    #[test]
+    fn error_position()
+    {
+        match parse_txt("1 2\n3 x4\n", "a.txt")
+        {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (2, 3)),
+            _ => panic!("expected a parse error"),
+        }
+
+        assert!(matches!(parse_txt("1\n", "a.txt"), Err(Error::Parse { line: 1, column: 2, .. })));
+    }
+
+    #[test]
+    fn separators_and_comments()
+    {
+        let points: Vec<Complex> = parse_txt("# exported\n1,2\n3\t4  # trailing\n 5 , 6 \n", "a.txt").unwrap();
+
+        assert_eq!(points, Vec::from([Complex::new(1.0, 2.0), Complex::new(3.0, 4.0), Complex::new(5.0, 6.0)]));
+    }
+
+    #[test]
+    fn header_and_strokes()
+    {
+        let path: TextPath = parse_text_path("name: line\nclosed: false\nunits: mm\n0 0\n1 0\n\n2 0\n", "a.txt").unwrap();
+
+        assert_eq!(path.name, Some(String::from("line")));
+        assert_eq!(path.units, Some(String::from("mm")));
+        assert_eq!(path.strokes.len(), 2);
+
+        // retraced back to the start
+        let xs: Vec<f32> = path.to_shape().iter().map(|p| p.real).collect();
+        assert_eq!(xs, Vec::from([0.0, 1.0, 2.0, 1.0]));
+
+        // the column of the value, wherever it starts after the colon
+        assert!(matches!(parse_text_path("closed: maybe\n0 0\n", "a.txt"), Err(Error::Parse { line: 1, column: 9, .. })));
+        assert!(matches!(parse_text_path("open:   maybe\n0 0\n", "a.txt"), Err(Error::Parse { line: 1, column: 9, .. })));
+    }
+
+    #[test]
+    fn csv_columns()
+    {
+        let points: Vec<Complex> = parse_txt("index,\"y\",\"x\"\n0,2,1\n1,4,3\n", "a.csv").unwrap();
+
+        assert_eq!(points, Vec::from([Complex::new(1.0, 2.0), Complex::new(3.0, 4.0)]));
+        assert!(parse_txt("colour: red\n", "a.txt").is_err());
+        assert!(matches!(parse_txt("# exported\nt,a,b\n0,1,2\n", "a.csv"), Err(Error::Parse { line: 2, column: 1, .. })));
+    }
+
+    #[test]
+    fn coefficients()
+    {
+        let epicycles: Vec<Epicycle> = vec![Epicycle::new(Complex::new(1.5, -2.0), 1.0), Epicycle::new(Complex::new(0.25, 0.0), -3.0)];
+        let contents: String = format_coefficients(&epicycles);
+
+        assert_eq!(format_from_bytes(contents.as_bytes()), PathFormat::Coefficients);
+        let parsed: Vec<Epicycle> = parse_coefficients(&contents, "a.coef").unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.iter().zip(&epicycles).all(|(a, b)| a.f == b.f && a.c0 == b.c0));
+        assert!(matches!(parse_coefficients("1 2\n", "a.coef"), Err(Error::Parse { line: 1, column: 1, .. })));
+        assert!(matches!(parse_coefficients("1 2 x\n", "a.coef"), Err(Error::Parse { line: 1, column: 5, .. })));
+    }
+
+    #[test]
+    fn magic_bytes()
+    {
+        assert_eq!(format_from_bytes(b"DWCP\x01\x00"), PathFormat::Binary);
+        assert_eq!(format_from_bytes(b"BM6\x00"), PathFormat::Image);
+        assert_eq!(format_from_bytes(b"P5\n4 4\n255\n"), PathFormat::Image);
+        assert_eq!(format_from_bytes(b"  <?xml version=\"1.0\"?><svg>"), PathFormat::Svg);
+        assert_eq!(format_from_bytes(b"<SVG viewBox=\"0 0 1 1\">"), PathFormat::Svg);
+        assert_eq!(format_from_bytes(b"1 2\n3 4\n"), PathFormat::Text);
+
+        // bitmaps (P1, P4) are not read by the image loader
+        assert_ne!(format_from_bytes(b"P1\n2 2\n0 1\n1 0\n"), PathFormat::Image);
+        assert_ne!(format_from_bytes(b"P4\n8 1\n\xff"), PathFormat::Image);
+
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("dwc_detect_{}", std::process::id()));
+        let file: String = path.to_string_lossy().to_string();
+        std::fs::write(&path, "<svg></svg>").unwrap();
+        assert_eq!(detect_format(&file).unwrap(), PathFormat::Svg);
+        assert_eq!(detect_format("shape.CSV").unwrap(), PathFormat::Text);
+        std::fs::remove_file(&path).unwrap();
+    }
 }
\ No newline at end of file
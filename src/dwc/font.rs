@@ -0,0 +1,139 @@
+// built-in 5x7 bitmap font, one byte per row with the leftmost pixel in bit 4
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+pub const GLYPH_SPACING: usize = 1;
+pub const LINE_SPACING: usize = 3;
+
+// lower case letters are drawn in upper case, unknown characters as '?'
+pub fn glyph(c: char) -> [u8; GLYPH_HEIGHT]
+{
+    return match c.to_ascii_uppercase()
+    {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        ' ' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ',' => [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '+' => [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000],
+        '=' => [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000],
+        '/' => [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000],
+        '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '_' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111],
+        '!' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100],
+        '\'' => [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100],
+    };
+}
+
+// font pixels lit by the text, in font pixels from its top left corner, '\n' starts a new line
+pub fn text_pixels(text: &str) -> Vec<(usize, usize)>
+{
+    let mut pixels: Vec<(usize, usize)> = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate()
+    {
+        let top: usize = line_index * (GLYPH_HEIGHT + LINE_SPACING);
+
+        for (char_index, c) in line.chars().enumerate()
+        {
+            let left: usize = char_index * (GLYPH_WIDTH + GLYPH_SPACING);
+
+            for (y, row) in glyph(c).iter().enumerate()
+            {
+                for x in 0..GLYPH_WIDTH
+                {
+                    if row >> (GLYPH_WIDTH - 1 - x) & 1 == 1
+                    {
+                        pixels.push((left + x, top + y));
+                    }
+                }
+            }
+        }
+    }
+
+    return pixels;
+}
+
+// width and height of the text in font pixels
+pub fn text_size(text: &str) -> (usize, usize)
+{
+    let columns: usize = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let rows: usize = text.lines().count();
+
+    return ((columns * (GLYPH_WIDTH + GLYPH_SPACING)).saturating_sub(GLYPH_SPACING),
+            (rows * (GLYPH_HEIGHT + LINE_SPACING)).saturating_sub(LINE_SPACING));
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn letter_pixels()
+    {
+        // 'L' is a full column and a bottom row sharing a corner
+        assert_eq!(text_pixels("L").len(), 7 + 4);
+        assert_eq!(text_pixels("l"), text_pixels("L"));
+    }
+
+    #[test]
+    fn lines_and_columns()
+    {
+        let pixels: Vec<(usize, usize)> = text_pixels("1\n.1");
+
+        assert!(pixels.contains(&(2, 0)));
+        assert!(pixels.contains(&(8, 10)));
+        assert_eq!(text_size("1\n.1"), (11, 17));
+    }
+
+    #[test]
+    fn unknown_character()
+    {
+        // a question mark
+        let fallback: [u8; GLYPH_HEIGHT] = [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100];
+
+        assert_eq!(glyph('~'), fallback);
+        assert_eq!(glyph('\u{e9}'), fallback);
+        assert_ne!(glyph('A'), fallback);
+        assert_ne!(glyph(' '), fallback);
+    }
+}
\ No newline at end of file
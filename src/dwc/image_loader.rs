@@ -0,0 +1,268 @@
+extern crate sdl2;
+
+use std::fs;
+
+use crate::dwc::error::*;
+
+use sdl2::surface::Surface;
+use sdl2::rwops::RWops;
+use sdl2::pixels::PixelFormatEnum;
+
+// single channel image, pixel values go from 0 (black) to 1 (white)
+#[derive(Clone)]
+pub struct GrayImage
+{
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<f32>
+}
+
+// gray image methods
+impl GrayImage
+{
+    pub fn new(width: usize, height: usize) -> GrayImage
+    {
+        return GrayImage { width: width, height: height, pixels: vec![0.0; width * height] };
+    }
+}
+
+impl GrayImage
+{
+    pub fn get(&self, x: usize, y: usize) -> f32
+    {
+        return self.pixels[y * self.width + x];
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: f32)
+    {
+        self.pixels[y * self.width + x] = value;
+    }
+}
+
+fn luminance(r: f32, g: f32, b: f32) -> f32
+{
+    return 0.299 * r + 0.587 * g + 0.114 * b;
+}
+
+// splits a netpbm header into tokens, skipping '#' comments
+struct PnmReader<'a>
+{
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PnmReader<'a>
+{
+    fn token(&mut self) -> Result<&'a str, String>
+    {
+        loop
+        {
+            while self.pos < self.data.len() && self.data[self.pos].is_ascii_whitespace()
+            {
+                self.pos += 1;
+            }
+
+            if self.pos < self.data.len() && self.data[self.pos] == b'#'
+            {
+                while self.pos < self.data.len() && self.data[self.pos] != b'\n'
+                {
+                    self.pos += 1;
+                }
+            }
+            else
+            {
+                break;
+            }
+        }
+
+        let start: usize = self.pos;
+
+        while self.pos < self.data.len() && !self.data[self.pos].is_ascii_whitespace()
+        {
+            self.pos += 1;
+        }
+
+        if start == self.pos
+        {
+            return Err(String::from("unexpected end of image"));
+        }
+
+        return std::str::from_utf8(&self.data[start..self.pos]).map_err(|_| String::from("invalid image header"));
+    }
+
+    fn number(&mut self) -> Result<usize, String>
+    {
+        let token: &str = self.token()?;
+
+        return token.parse::<usize>().map_err(|_| format!("invalid number '{}' in image", token));
+    }
+}
+
+// parse a netpbm image: P2/P5 (graymap) or P3/P6 (pixmap), in ascii or binary form
+pub fn parse_pnm(data: &[u8]) -> Result<GrayImage, String>
+{
+    let mut reader: PnmReader = PnmReader { data: data, pos: 0 };
+
+    let magic: &str = reader.token()?;
+    let channels: usize = match magic
+    {
+        "P2" | "P5" => 1,
+        "P3" | "P6" => 3,
+        _ => return Err(format!("unsupported netpbm format '{}'", magic)),
+    };
+    let binary: bool = magic == "P5" || magic == "P6";
+
+    let width: usize = reader.number()?;
+    let height: usize = reader.number()?;
+    let max_value: usize = reader.number()?;
+
+    if max_value == 0 || max_value > 65535
+    {
+        return Err(format!("invalid maximum value {}", max_value));
+    }
+
+    // a single whitespace separates the header from binary data
+    reader.pos += 1;
+
+    let bytes_per_sample: usize = if max_value < 256 { 1 } else { 2 };
+
+    // every sample takes at least one byte, even in ascii, so a size beyond the data is a broken header
+    let remaining: usize = data.len().saturating_sub(reader.pos);
+    let sample_size: usize = if binary { bytes_per_sample } else { 1 };
+    let sample_count: usize = width.checked_mul(height)
+                                   .and_then(|n| n.checked_mul(channels))
+                                   .filter(|n| n.checked_mul(sample_size).is_some_and(|size| size <= remaining))
+                                   .ok_or_else(|| format!("image size {}x{} does not match its data", width, height))?;
+
+    let mut samples: Vec<f32> = Vec::with_capacity(sample_count);
+
+    for _ in 0..sample_count
+    {
+        let sample: usize = if binary
+        {
+            let start: usize = reader.pos;
+            reader.pos += bytes_per_sample;
+
+            if reader.pos > data.len()
+            {
+                return Err(String::from("unexpected end of image"));
+            }
+
+            if bytes_per_sample == 1 { data[start] as usize } else { (data[start] as usize) << 8 | data[start + 1] as usize }
+        }
+        else
+        {
+            reader.number()?
+        };
+
+        samples.push(sample.min(max_value) as f32 / max_value as f32);
+    }
+
+    let mut image: GrayImage = GrayImage::new(width, height);
+
+    for i in 0..width * height
+    {
+        image.pixels[i] = if channels == 1
+        {
+            samples[i]
+        }
+        else
+        {
+            luminance(samples[i * 3], samples[i * 3 + 1], samples[i * 3 + 2])
+        };
+    }
+
+    return Ok(image);
+}
+
+pub fn load_pnm(path: &str) -> Result<GrayImage, Error>
+{
+    let data: Vec<u8> = fs::read(path).map_err(|e| Error::io(path, e))?;
+
+    return parse_pnm(&data).map_err(|e| Error::format(path, &e));
+}
+
+// decode a bmp through sdl, whatever its pixel format
+pub fn parse_bmp(data: &[u8]) -> Result<GrayImage, String>
+{
+    let mut rw: RWops = RWops::from_bytes(data)?;
+    let surface: Surface = Surface::load_bmp_rw(&mut rw)?;
+    let surface: Surface = surface.convert_format(PixelFormatEnum::RGBA32)?;
+
+    let width: usize = surface.width() as usize;
+    let height: usize = surface.height() as usize;
+    let pitch: usize = surface.pitch() as usize;
+    let mut image: GrayImage = GrayImage::new(width, height);
+
+    surface.with_lock(|data: &[u8]|
+    {
+        for y in 0..height
+        {
+            for x in 0..width
+            {
+                let i: usize = y * pitch + x * 4;
+                let alpha: f32 = data[i + 3] as f32 / 255.0;
+                let value: f32 = luminance(data[i] as f32, data[i + 1] as f32, data[i + 2] as f32) / 255.0;
+
+                // transparent pixels count as white background
+                image.set(x, y, value * alpha + (1.0 - alpha));
+            }
+        }
+    });
+
+    return Ok(image);
+}
+
+// bmp or netpbm, told apart by their first bytes
+pub fn load_image(path: &str) -> Result<GrayImage, Error>
+{
+    let data: Vec<u8> = fs::read(path).map_err(|e| Error::io(path, e))?;
+
+    if data.starts_with(b"BM")
+    {
+        return parse_bmp(&data).map_err(|e| Error::format(path, &e));
+    }
+
+    return parse_pnm(&data).map_err(|e| Error::format(path, &e));
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn ascii_graymap()
+    {
+        let image: GrayImage = parse_pnm(b"P2\n# comment\n2 2\n4\n0 4\n2 4\n").unwrap();
+
+        assert_eq!(image.width, 2);
+        assert_eq!(image.pixels, Vec::from([0.0, 1.0, 0.5, 1.0]));
+    }
+
+    #[test]
+    fn binary_pixmap()
+    {
+        let mut data: Vec<u8> = Vec::from(&b"P6 2 1 255\n"[..]);
+        data.extend([255, 255, 255, 0, 0, 0]);
+
+        let image: GrayImage = parse_pnm(&data).unwrap();
+
+        assert!((image.get(0, 0) - 1.0).abs() < 0.001);
+        assert_eq!(image.get(1, 0), 0.0);
+    }
+
+    #[test]
+    fn truncated_image()
+    {
+        assert!(parse_pnm(b"P5 4 4 255\n\x00\x00").is_err());
+    }
+
+    #[test]
+    fn oversized_header()
+    {
+        assert!(parse_pnm(b"P5 99999999 99999999 255\n\x00").is_err());
+        assert!(parse_pnm(b"P6 18446744073709551615 2 255\n\x00").is_err());
+        assert!(parse_pnm(b"P2 1000 1000 255\n0 1 2\n").is_err());
+    }
+}
\ No newline at end of file
@@ -1,13 +1,57 @@
 mod common;
+mod error;
 mod complex;
 mod epicycle;
 mod file_loader;
+mod binary_path;
+mod svg_loader;
+mod image_loader;
+mod contour;
+mod edges;
+mod tour;
+mod path;
 mod draw;
+mod trail;
+mod camera;
+mod raster;
+mod trail_style;
+mod theme;
+mod playback;
+mod convergence;
+mod font;
+mod watch;
+mod browser;
+mod screenshot;
+mod settings;
+mod cli;
+mod config;
 mod app;
 
 pub use self::common::*;
+pub use self::error::*;
 pub use self::complex::*;
 pub use self::epicycle::*;
 pub use self::file_loader::*;
+pub use self::binary_path::*;
+pub use self::svg_loader::*;
+pub use self::image_loader::*;
+pub use self::contour::*;
+pub use self::edges::*;
+pub use self::tour::*;
+pub use self::path::*;
 pub use self::draw::*;
+pub use self::trail::*;
+pub use self::camera::*;
+pub use self::raster::*;
+pub use self::trail_style::*;
+pub use self::theme::*;
+pub use self::playback::*;
+pub use self::convergence::*;
+pub use self::font::*;
+pub use self::watch::*;
+pub use self::browser::*;
+pub use self::screenshot::*;
+pub use self::settings::*;
+pub use self::cli::*;
+pub use self::config::*;
 pub use self::app::*;
\ No newline at end of file
@@ -0,0 +1,205 @@
+use crate::dwc::complex::*;
+
+// axis aligned box around a set of points
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Bounds
+{
+    pub min: Complex,
+    pub max: Complex
+}
+
+// bounds methods
+impl Bounds
+{
+    pub fn new(min: Complex, max: Complex) -> Bounds
+    {
+        return Bounds { min: min, max: max };
+    }
+
+    // none when there are no points
+    pub fn from_points(points: &[Complex]) -> Option<Bounds>
+    {
+        let first: &Complex = points.first()?;
+        let mut bounds: Bounds = Bounds::new(first.clone(), first.clone());
+
+        for p in points
+        {
+            bounds.include(p);
+        }
+
+        return Some(bounds);
+    }
+}
+
+impl Bounds
+{
+    pub fn include(&mut self, p: &Complex)
+    {
+        self.min = Complex::new(self.min.real.min(p.real), self.min.img.min(p.img));
+        self.max = Complex::new(self.max.real.max(p.real), self.max.img.max(p.img));
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds
+    {
+        let mut bounds: Bounds = self.clone();
+        bounds.include(&other.min);
+        bounds.include(&other.max);
+
+        return bounds;
+    }
+
+    pub fn center(&self) -> Complex
+    {
+        return (&self.min + &self.max) * 0.5;
+    }
+
+    pub fn width(&self) -> f32
+    {
+        return self.max.real - self.min.real;
+    }
+
+    pub fn height(&self) -> f32
+    {
+        return self.max.img - self.min.img;
+    }
+}
+
+pub fn centroid(points: &[Complex]) -> Complex
+{
+    let mut sum: Complex = Complex::new(0.0, 0.0);
+
+    for p in points
+    {
+        sum += p;
+    }
+
+    return sum * (1.0 / points.len().max(1) as f32);
+}
+
+// true when every point is at the same place
+pub fn is_degenerate(points: &[Complex]) -> bool
+{
+    return points.iter().all(|p| p.real == points[0].real && p.img == points[0].img);
+}
+
+// join separate strokes into one continuous shape (the pen jumps between strokes)
+pub fn join_strokes(strokes: &[Vec<Complex>]) -> Vec<Complex>
+{
+    let mut points: Vec<Complex> = Vec::new();
+
+    for stroke in strokes
+    {
+        points.extend(stroke.iter().cloned());
+    }
+
+    return points;
+}
+
+// total length of the closed shape (including the segment back to the start)
+pub fn closed_length(points: &[Complex]) -> f32
+{
+    let mut length: f32 = 0.0;
+
+    for i in 0..points.len()
+    {
+        length += (&points[(i + 1) % points.len()] - &points[i]).magnitude();
+    }
+
+    return length;
+}
+
+// resample a closed shape into `count` points evenly spaced along its length
+// compute_epicycles assumes the points are sampled at a constant speed
+pub fn resample(points: &[Complex], count: usize) -> Vec<Complex>
+{
+    let mut samples: Vec<Complex> = Vec::new();
+    let length: f32 = closed_length(points);
+
+    if points.len() < 2 || count == 0 || length <= 0.0
+    {
+        return points.to_vec();
+    }
+
+    let step: f32 = length / count as f32;
+    let mut segment: usize = 0;
+    let mut segment_start: f32 = 0.0; // distance travelled at the start of the current segment
+
+    for i in 0..count
+    {
+        let distance: f32 = step * i as f32;
+        let mut a: &Complex = &points[segment];
+        let mut b: &Complex = &points[(segment + 1) % points.len()];
+        let mut segment_length: f32 = (b - a).magnitude();
+
+        while segment_start + segment_length < distance && segment + 1 < points.len()
+        {
+            segment_start += segment_length;
+            segment += 1;
+            a = &points[segment];
+            b = &points[(segment + 1) % points.len()];
+            segment_length = (b - a).magnitude();
+        }
+
+        if segment_length > 0.0
+        {
+            let s: f32 = ((distance - segment_start) / segment_length).clamp(0.0, 1.0);
+            samples.push(a + (b - a) * s);
+        }
+        else
+        {
+            samples.push(a.clone());
+        }
+    }
+
+    return samples;
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn join()
+    {
+        let strokes: Vec<Vec<Complex>> = Vec::from
+        ([
+            Vec::from([Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)]),
+            Vec::from([Complex::new(2.0, 0.0)]),
+        ]);
+
+        assert_eq!(join_strokes(&strokes).len(), 3);
+    }
+
+    #[test]
+    fn bounds()
+    {
+        let points: Vec<Complex> = Vec::from([Complex::new(1.0, -2.0), Complex::new(-3.0, 4.0), Complex::new(0.0, 0.0)]);
+        let bounds: Bounds = Bounds::from_points(&points).unwrap();
+
+        assert_eq!(bounds, Bounds::new(Complex::new(-3.0, -2.0), Complex::new(1.0, 4.0)));
+        assert_eq!(bounds.center(), Complex::new(-1.0, 1.0));
+        assert!(Bounds::from_points(&Vec::new()).is_none());
+    }
+
+    #[test]
+    fn resample_square()
+    {
+        let square: Vec<Complex> = Vec::from
+        ([
+            Complex::new(0.0, 0.0),
+            Complex::new(1.0, 0.0),
+            Complex::new(1.0, 1.0),
+            Complex::new(0.0, 1.0),
+        ]);
+
+        let samples: Vec<Complex> = resample(&square, 8);
+
+        assert_eq!(samples.len(), 8);
+        assert!(complex_eq(samples[1].clone(), Complex::new(0.5, 0.0)));
+        assert!(complex_eq(samples[3].clone(), Complex::new(1.0, 0.5)));
+        assert!(complex_eq(samples[7].clone(), Complex::new(0.0, 0.5)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,137 @@
+pub const TICK: f32 = 1.0 / 60.0; // seconds simulated by every update, whatever the frame rate
+pub const MIN_SPEED: f32 = 1.0 / 64.0;
+pub const MAX_SPEED: f32 = 64.0;
+const SPEED_STEP: f32 = 1.5;
+
+// where the animation is in time and how it moves, t is counted in periods of the drawing
+pub struct Playback
+{
+    pub t: f32,
+    pub rate: f32, // periods per second at normal speed
+    pub speed: f32,
+    pub reversed: bool,
+    pub paused: bool
+}
+
+// playback methods
+impl Playback
+{
+    // starts paused
+    pub fn new(rate: f32) -> Playback
+    {
+        return Playback { t: 0.0, rate: rate, speed: 1.0, reversed: false, paused: true };
+    }
+}
+
+impl Playback
+{
+    // signed change of t over one tick
+    pub fn step(&self) -> f32
+    {
+        let step: f32 = self.rate * self.speed * TICK;
+
+        return if self.reversed { -step } else { step };
+    }
+
+    // move on by one tick unless paused
+    pub fn advance(&mut self)
+    {
+        if !self.paused
+        {
+            self.t += self.step();
+        }
+    }
+
+    // move by whole ticks even when paused, negative counts go back in time whatever the direction of playback
+    pub fn step_frames(&mut self, count: i32)
+    {
+        self.t += self.rate * self.speed * TICK * count as f32;
+    }
+
+    pub fn toggle_pause(&mut self)
+    {
+        self.paused = !self.paused;
+    }
+
+    pub fn faster(&mut self)
+    {
+        self.speed = (self.speed * SPEED_STEP).min(MAX_SPEED);
+    }
+
+    pub fn slower(&mut self)
+    {
+        self.speed = (self.speed / SPEED_STEP).max(MIN_SPEED);
+    }
+
+    pub fn reverse(&mut self)
+    {
+        self.reversed = !self.reversed;
+    }
+
+    // position within the current period, from 0 to 1
+    pub fn progress(&self) -> f32
+    {
+        return self.t.rem_euclid(1.0);
+    }
+
+    // jump to a position within the current period
+    pub fn seek(&mut self, progress: f32)
+    {
+        self.t = self.t.floor() + progress.clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn paused_until_started()
+    {
+        let mut playback: Playback = Playback::new(15.0);
+        playback.advance();
+        assert_eq!(playback.t, 0.0);
+
+        playback.toggle_pause();
+        playback.advance();
+        assert!((playback.t - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reverse_and_step()
+    {
+        let mut playback: Playback = Playback::new(15.0);
+        playback.reverse();
+        playback.step_frames(-2);
+
+        assert!((playback.t + 0.5).abs() < 1e-5);
+        assert!((playback.progress() - 0.5).abs() < 1e-5);
+
+        playback.step_frames(3);
+        assert!((playback.t - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn speed_limits()
+    {
+        let mut playback: Playback = Playback::new(15.0);
+
+        for _ in 0..100
+        {
+            playback.faster();
+        }
+
+        assert_eq!(playback.speed, MAX_SPEED);
+    }
+
+    #[test]
+    fn seek_keeps_period()
+    {
+        let mut playback: Playback = Playback::new(15.0);
+        playback.t = 3.7;
+        playback.seek(0.25);
+
+        assert!((playback.t - 3.25).abs() < 1e-5);
+    }
+}
\ No newline at end of file
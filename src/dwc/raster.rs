@@ -0,0 +1,414 @@
+extern crate sdl2;
+
+use std::collections::HashMap;
+
+use sdl2::video::Window;
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+use sdl2::render::Canvas;
+use sdl2::render::BlendMode;
+
+const COVERAGE_LEVELS: f32 = 16.0; // alpha steps used when batching sdl points
+
+// anything antialiased lines can be drawn onto
+pub trait PixelTarget
+{
+    // blend `color` into the pixel, `coverage` (0 to 1) scales the colour's alpha
+    fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: f32);
+
+    // drawable width and height, lines are clipped to it
+    fn size(&self) -> (f32, f32);
+}
+
+// software rgba framebuffer, blends like the sdl canvas does
+pub struct Framebuffer
+{
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Color>,
+    pub blend_mode: BlendMode
+}
+
+// draws through an sdl canvas, pixels with the same colour and coverage are drawn in one call
+pub struct CanvasTarget<'a>
+{
+    canvas: &'a mut Canvas<Window>,
+    batches: HashMap<(u8, u8, u8, u8), Vec<Point>>
+}
+
+// framebuffer methods
+impl Framebuffer
+{
+    pub fn new(width: u32, height: u32) -> Framebuffer
+    {
+        return Framebuffer { width: width,
+                             height: height,
+                             pixels: vec![Color::RGBA(0, 0, 0, 255); (width * height) as usize],
+                             blend_mode: BlendMode::Add };
+    }
+}
+
+impl Framebuffer
+{
+    pub fn clear(&mut self, color: Color)
+    {
+        for pixel in self.pixels.iter_mut()
+        {
+            *pixel = color;
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Color
+    {
+        return self.pixels[(y * self.width + x) as usize];
+    }
+}
+
+impl PixelTarget for Framebuffer
+{
+    fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: f32)
+    {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32
+        {
+            return;
+        }
+
+        let alpha: f32 = color.a as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+        let index: usize = (y as u32 * self.width + x as u32) as usize;
+        let dst: Color = self.pixels[index];
+
+        let mix = |src: u8, dst: u8| -> u8
+        {
+            let value: f32 = match self.blend_mode
+            {
+                BlendMode::Add => dst as f32 + src as f32 * alpha,
+                BlendMode::Blend => dst as f32 * (1.0 - alpha) + src as f32 * alpha,
+                _ => src as f32,
+            };
+
+            return value.round().clamp(0.0, 255.0) as u8;
+        };
+
+        self.pixels[index] = Color::RGBA(mix(color.r, dst.r), mix(color.g, dst.g), mix(color.b, dst.b), dst.a);
+    }
+
+    fn size(&self) -> (f32, f32)
+    {
+        return (self.width as f32, self.height as f32);
+    }
+}
+
+// canvas target methods
+impl<'a> CanvasTarget<'a>
+{
+    pub fn new(canvas: &'a mut Canvas<Window>) -> CanvasTarget<'a>
+    {
+        return CanvasTarget { canvas: canvas, batches: HashMap::new() };
+    }
+}
+
+impl<'a> CanvasTarget<'a>
+{
+    // draw everything blended so far
+    pub fn flush(&mut self)
+    {
+        for ((r, g, b, a), points) in self.batches.drain()
+        {
+            self.canvas.set_draw_color(Color::RGBA(r, g, b, a));
+            let _ = self.canvas.draw_points(&points[..]);
+        }
+    }
+
+    // aliased one pixel line, drawn straight away
+    pub fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: Color)
+    {
+        self.canvas.set_draw_color(color);
+        let _ = self.canvas.draw_line(Point::new(from.0 as i32, from.1 as i32), Point::new(to.0 as i32, to.1 as i32));
+    }
+}
+
+impl<'a> PixelTarget for CanvasTarget<'a>
+{
+    fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: f32)
+    {
+        let level: f32 = (coverage.clamp(0.0, 1.0) * COVERAGE_LEVELS).round() / COVERAGE_LEVELS;
+        let alpha: u8 = (color.a as f32 * level).round() as u8;
+
+        if alpha == 0
+        {
+            return;
+        }
+
+        self.batches.entry((color.r, color.g, color.b, alpha)).or_default().push(Point::new(x, y));
+    }
+
+    fn size(&self) -> (f32, f32)
+    {
+        let (width, height) = self.canvas.output_size().unwrap_or((0, 0));
+        let (scale_x, scale_y) = self.canvas.scale();
+
+        return (width as f32 / scale_x, height as f32 / scale_y);
+    }
+}
+
+// cut the segment down to the part inside the target, grown by `margin` (liang-barsky)
+pub fn clip_segment(from: (f32, f32), to: (f32, f32), size: (f32, f32), margin: f32) -> Option<((f32, f32), (f32, f32))>
+{
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+
+    // each edge as p * t <= q
+    let edges: [(f32, f32); 4] = [(-dx, from.0 + margin), (dx, size.0 + margin - from.0),
+                                  (-dy, from.1 + margin), (dy, size.1 + margin - from.1)];
+
+    for (p, q) in edges
+    {
+        if p == 0.0
+        {
+            if q < 0.0
+            {
+                return None;
+            }
+        }
+        else if p < 0.0
+        {
+            t0 = t0.max(q / p);
+        }
+        else
+        {
+            t1 = t1.min(q / p);
+        }
+    }
+
+    if t0 > t1
+    {
+        return None;
+    }
+
+    return Some(((from.0 + dx * t0, from.1 + dy * t0), (from.0 + dx * t1, from.1 + dy * t1)));
+}
+
+impl<'a> Drop for CanvasTarget<'a>
+{
+    fn drop(&mut self)
+    {
+        self.flush();
+    }
+}
+
+// one pixel wide antialiased line (xiaolin wu's algorithm)
+pub fn draw_line_aa<T: PixelTarget>(target: &mut T, from: (f32, f32), to: (f32, f32), color: Color)
+{
+    let (from, to) = match clip_segment(from, to, target.size(), 2.0) { Some(segment) => segment, None => return };
+
+    // the centre of pixel (x, y) is at (x + 0.5, y + 0.5), wu works with centres on integers
+    let (mut x0, mut y0, mut x1, mut y1) = (from.0 - 0.5, from.1 - 0.5, to.0 - 0.5, to.1 - 0.5);
+    let steep: bool = (y1 - y0).abs() > (x1 - x0).abs();
+
+    // always walk along the longer axis, from left to right
+    if steep
+    {
+        std::mem::swap(&mut x0, &mut y0);
+        std::mem::swap(&mut x1, &mut y1);
+    }
+
+    if x0 > x1
+    {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let gradient: f32 = if x1 - x0 > 0.0 { (y1 - y0) / (x1 - x0) } else { 1.0 };
+
+    let mut plot = |x: i32, y: i32, coverage: f32|
+    {
+        if steep { target.blend_pixel(y, x, color, coverage); } else { target.blend_pixel(x, y, color, coverage); }
+    };
+
+    let start: i32 = x0.round() as i32;
+    let end: i32 = x1.round() as i32;
+
+    for x in start..end + 1
+    {
+        // the end pixels are only partly covered along the line
+        let mut weight: f32 = 1.0;
+
+        if x == start
+        {
+            weight = 1.0 - (x0 + 0.5 - start as f32);
+        }
+
+        if x == end
+        {
+            weight = if start == end { x1 - x0 } else { x1 + 0.5 - end as f32 };
+        }
+
+        let y: f32 = y0 + gradient * (x as f32 - x0);
+        let fract: f32 = y - y.floor();
+
+        plot(x, y.floor() as i32, (1.0 - fract) * weight.clamp(0.0, 1.0));
+        plot(x, y.floor() as i32 + 1, fract * weight.clamp(0.0, 1.0));
+    }
+}
+
+// x range of the row y covered by a capsule of radius r around the segment a-b
+fn capsule_span(a: (f32, f32), b: (f32, f32), r: f32, y: f32) -> Option<(f32, f32)>
+{
+    let mut span: Option<(f32, f32)> = None;
+    let mut include = |lo: f32, hi: f32|
+    {
+        if lo <= hi
+        {
+            span = Some(match span { Some((l, h)) => (l.min(lo), h.max(hi)), None => (lo, hi) });
+        }
+    };
+
+    // round caps
+    for p in [a, b]
+    {
+        let dy: f32 = y - p.1;
+
+        if dy.abs() <= r
+        {
+            let dx: f32 = (r * r - dy * dy).sqrt();
+            include(p.0 - dx, p.0 + dx);
+        }
+    }
+
+    // the body, where the projection is on the segment and the distance to the line is below r
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length: f32 = (dx * dx + dy * dy).sqrt();
+
+    if length > 0.0
+    {
+        let (ux, uy) = (dx / length, dy / length);
+        let mut lo: f32 = f32::MIN;
+        let mut hi: f32 = f32::MAX;
+
+        // each constraint is c0 <= k * x + c <= c1 for the points (x, y)
+        for (k, c, c0, c1) in [(ux, uy * (y - a.1) - ux * a.0, 0.0, length), (-uy, ux * (y - a.1) + uy * a.0, -r, r)]
+        {
+            if k.abs() > 1e-6
+            {
+                let (x0, x1) = ((c0 - c) / k, (c1 - c) / k);
+                lo = lo.max(x0.min(x1));
+                hi = hi.min(x0.max(x1));
+            }
+            else if c < c0 || c > c1
+            {
+                hi = f32::MIN;
+            }
+        }
+
+        include(lo, hi);
+    }
+
+    return span;
+}
+
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32
+{
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq: f32 = dx * dx + dy * dy;
+    let t: f32 = if length_sq > 0.0 { (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+
+    return ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt();
+}
+
+// antialiased line of any width with round caps, coverage comes from the distance to the segment
+pub fn draw_thick_line<T: PixelTarget>(target: &mut T, from: (f32, f32), to: (f32, f32), width: f32, color: Color)
+{
+    let radius: f32 = width * 0.5;
+    let reach: f32 = radius + 0.5; // pixels closer than this are at least partly covered
+
+    let (from, to) = match clip_segment(from, to, target.size(), reach + 1.0) { Some(segment) => segment, None => return };
+
+    let top: i32 = (from.1.min(to.1) - reach).floor() as i32;
+    let bottom: i32 = (from.1.max(to.1) + reach).ceil() as i32;
+
+    for y in top..bottom + 1
+    {
+        let center_y: f32 = y as f32 + 0.5;
+
+        if let Some((lo, hi)) = capsule_span(from, to, reach, center_y)
+        {
+            for x in (lo - 0.5).floor() as i32..(hi - 0.5).ceil() as i32 + 1
+            {
+                let distance: f32 = segment_distance((x as f32 + 0.5, center_y), from, to);
+                let coverage: f32 = (reach - distance).clamp(0.0, 1.0);
+
+                if coverage > 0.0
+                {
+                    target.blend_pixel(x, y, color, coverage);
+                }
+            }
+        }
+    }
+}
+
+// connected line segments, wu lines for hairlines and coverage lines for anything thicker
+pub fn draw_polyline<T: PixelTarget>(target: &mut T, points: &[(f32, f32)], width: f32, color: Color)
+{
+    for segment in points.windows(2)
+    {
+        if width <= 1.0
+        {
+            draw_line_aa(target, segment[0], segment[1], Color::RGBA(color.r, color.g, color.b, (color.a as f32 * width.max(0.0)) as u8));
+        }
+        else
+        {
+            draw_thick_line(target, segment[0], segment[1], width, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn coverage_sum(framebuffer: &Framebuffer) -> f32
+    {
+        return framebuffer.pixels.iter().map(|p| p.r as f32 / 255.0).sum();
+    }
+
+    #[test]
+    fn wu_line_covers_its_length()
+    {
+        let mut framebuffer: Framebuffer = Framebuffer::new(32, 32);
+        draw_line_aa(&mut framebuffer, (2.5, 2.5), (22.5, 12.5), Color::RGB(255, 255, 255));
+
+        // about one pixel of coverage for every step along the x axis
+        let total: f32 = coverage_sum(&framebuffer);
+        assert!(total > 19.0 && total < 22.0);
+    }
+
+    #[test]
+    fn thick_line_area()
+    {
+        let mut framebuffer: Framebuffer = Framebuffer::new(64, 64);
+        draw_thick_line(&mut framebuffer, (10.0, 10.0), (50.0, 40.0), 4.0, Color::RGB(255, 255, 255));
+
+        // 50 long and 4 wide, plus two half discs
+        let expected: f32 = 50.0 * 4.0 + std::f32::consts::PI * 4.0;
+        let total: f32 = coverage_sum(&framebuffer);
+        assert!((total - expected).abs() < expected * 0.05);
+    }
+
+    #[test]
+    fn out_of_bounds_pixels_are_ignored()
+    {
+        let mut framebuffer: Framebuffer = Framebuffer::new(8, 8);
+        draw_thick_line(&mut framebuffer, (-20.0, 4.0), (30.0, 4.0), 2.0, Color::RGB(255, 255, 255));
+
+        assert_eq!(framebuffer.get(4, 4).r, 255);
+    }
+
+    #[test]
+    fn clipping()
+    {
+        assert_eq!(clip_segment((-10.0, 5.0), (20.0, 5.0), (10.0, 10.0), 0.0), Some(((0.0, 5.0), (10.0, 5.0))));
+        assert_eq!(clip_segment((-10.0, -5.0), (20.0, -5.0), (10.0, 10.0), 0.0), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,53 @@
+use std::time::SystemTime;
+
+// "yyyymmdd_hhmmss" in utc, for file names that sort by time
+pub fn timestamp(unix_seconds: u64) -> String
+{
+    let days: i64 = (unix_seconds / 86400) as i64;
+    let seconds: u64 = unix_seconds % 86400;
+
+    // civil date from the day count (howard hinnant's civil_from_days)
+    let z: i64 = days + 719468;
+    let era: i64 = z.div_euclid(146097);
+    let day_of_era: i64 = z - era * 146097;
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month: i64 = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year: i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return format!("{:04}{:02}{:02}_{:02}{:02}{:02}", year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60);
+}
+
+// a name in `dir` for a screenshot taken now, numbered when one was already taken this second
+pub fn screenshot_file(dir: &str) -> String
+{
+    let now: u64 = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let name: String = format!("{}screenshot_{}", dir, timestamp(now));
+
+    let mut file: String = format!("{}.bmp", name);
+    let mut index: usize = 2;
+
+    while std::path::Path::new(&file).exists()
+    {
+        file = format!("{}_{}.bmp", name, index);
+        index += 1;
+    }
+
+    return file;
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn dates()
+    {
+        assert_eq!(timestamp(0), "19700101_000000");
+        assert_eq!(timestamp(951782400), "20000229_000000");
+        assert_eq!(timestamp(1792422245), "20261019_150405");
+    }
+}
\ No newline at end of file
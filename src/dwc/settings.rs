@@ -0,0 +1,77 @@
+extern crate sdl2;
+
+use crate::dwc::contour::*;
+use crate::dwc::file_loader::*;
+use crate::dwc::theme::*;
+use crate::dwc::trail_style::*;
+
+use sdl2::pixels::Color;
+
+// what the program does with the loaded shape
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum OutputMode
+{
+    Window,
+    Fullscreen,
+    Coefficients, // print the coefficients of the series instead of opening a window
+}
+
+// everything that can be chosen when starting the program
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Settings
+{
+    pub file: String,
+    pub term_count: usize,
+    pub rate: f32, // periods per second
+    pub trail_length: usize,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub scale: Option<f32>, // None fits the drawing to the window
+    pub max_framerate: u32,
+    pub vsync: bool,
+    pub image_trace: ImageTrace,
+    pub theme: usize, // index into THEMES
+    pub circles: CircleColoring,
+    pub trail_style: TrailStyle,
+    pub trail_color: Option<Color>, // the colours below replace those of the theme
+    pub circle_color: Option<Color>,
+    pub background_color: Option<Color>,
+    pub antialiasing: bool, // lines wider than one pixel are always antialiased
+    pub trail_width: f32, // stroke widths in pixels
+    pub arm_width: f32,
+    pub circle_width: f32,
+    pub output: OutputMode
+}
+
+impl Default for Settings
+{
+    fn default() -> Settings
+    {
+        return Settings { file: String::from("./paths/default.txt"),
+                          term_count: 298,
+                          rate: 1.0 / 15.0,
+                          trail_length: 880,
+                          window_width: 1000,
+                          window_height: 800,
+                          scale: None,
+                          max_framerate: 60,
+                          vsync: true,
+                          image_trace: ImageTrace::Contours(ContourSelection::Largest),
+                          theme: 0,
+                          circles: CircleColoring::Uniform,
+                          trail_style: TrailStyle::new(TrailColoring::Solid, true),
+                          trail_color: None,
+                          circle_color: None,
+                          background_color: None,
+                          antialiasing: true,
+                          trail_width: 2.0,
+                          arm_width: 1.0,
+                          circle_width: 1.0,
+                          output: OutputMode::Window };
+    }
+}
\ No newline at end of file
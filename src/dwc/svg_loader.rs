@@ -0,0 +1,769 @@
+use std::fs;
+use crate::dwc::common::*;
+use crate::dwc::complex::*;
+use crate::dwc::error::*;
+
+// maximum distance (in user units) between a curve and its flattened polyline
+pub const SVG_FLATNESS: f32 = 0.25;
+const MAX_SUBDIVISION: u32 = 16;
+
+// 2d affine transform, maps (x, y) to (a*x + c*y + e, b*x + d*y + f) like svg's matrix()
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Transform
+{
+    pub a: f32, pub b: f32,
+    pub c: f32, pub d: f32,
+    pub e: f32, pub f: f32,
+}
+
+struct Tag
+{
+    name: String,
+    attributes: Vec<(String, String)>,
+    closing: bool, // </name>
+    self_closing: bool, // <name ... />
+}
+
+// splits a path's data into commands and numbers
+struct PathLexer<'a>
+{
+    data: &'a [u8],
+    pos: usize,
+}
+
+// transform methods
+impl Transform
+{
+    pub fn identity() -> Transform
+    {
+        return Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 };
+    }
+
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Transform
+    {
+        return Transform { a: a, b: b, c: c, d: d, e: e, f: f };
+    }
+}
+
+impl Transform
+{
+    // the transform that applies `rhs` first and then `self`
+    pub fn then(&self, rhs: &Transform) -> Transform
+    {
+        return Transform
+        {
+            a: self.a * rhs.a + self.c * rhs.b,
+            b: self.b * rhs.a + self.d * rhs.b,
+            c: self.a * rhs.c + self.c * rhs.d,
+            d: self.b * rhs.c + self.d * rhs.d,
+            e: self.a * rhs.e + self.c * rhs.f + self.e,
+            f: self.b * rhs.e + self.d * rhs.f + self.f,
+        };
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32)
+    {
+        return (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f);
+    }
+
+    // largest stretch the transform can apply to a length
+    pub fn max_scale(&self) -> f32
+    {
+        let sx: f32 = (self.a * self.a + self.b * self.b).sqrt();
+        let sy: f32 = (self.c * self.c + self.d * self.d).sqrt();
+
+        return sx.max(sy);
+    }
+}
+
+// parse a transform attribute such as "translate(10 20) rotate(45)"
+pub fn parse_transform(input: &str) -> Result<Transform, String>
+{
+    let mut transform: Transform = Transform::identity();
+    let mut rest: &str = input.trim();
+
+    while !rest.is_empty()
+    {
+        let open: usize = rest.find('(').ok_or(format!("invalid transform '{}'", input))?;
+        let close: usize = rest.find(')').ok_or(format!("invalid transform '{}'", input))?;
+
+        let name: &str = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+        let args: Vec<f32> = parse_number_list(&rest[open + 1..close])?;
+
+        let arg = |i: usize, default: f32| -> f32 { if i < args.len() { args[i] } else { default } };
+
+        let step: Transform = match name
+        {
+            "matrix" if args.len() == 6 => Transform::new(args[0], args[1], args[2], args[3], args[4], args[5]),
+            "translate" if !args.is_empty() => Transform::new(1.0, 0.0, 0.0, 1.0, args[0], arg(1, 0.0)),
+            "scale" if !args.is_empty() => Transform::new(args[0], 0.0, 0.0, arg(1, args[0]), 0.0, 0.0),
+            "rotate" if !args.is_empty() =>
+            {
+                let (sin, cos) = args[0].to_radians().sin_cos();
+                let (cx, cy) = (arg(1, 0.0), arg(2, 0.0));
+
+                Transform::new(1.0, 0.0, 0.0, 1.0, cx, cy)
+                    .then(&Transform::new(cos, sin, -sin, cos, 0.0, 0.0))
+                    .then(&Transform::new(1.0, 0.0, 0.0, 1.0, -cx, -cy))
+            },
+            "skewX" if args.len() == 1 => Transform::new(1.0, 0.0, args[0].to_radians().tan(), 1.0, 0.0, 0.0),
+            "skewY" if args.len() == 1 => Transform::new(1.0, args[0].to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => return Err(format!("invalid transform '{}'", input)),
+        };
+
+        transform = transform.then(&step);
+        rest = rest[close + 1..].trim_start();
+    }
+
+    return Ok(transform);
+}
+
+fn parse_number_list(input: &str) -> Result<Vec<f32>, String>
+{
+    let mut lexer: PathLexer = PathLexer::new(input);
+    let mut numbers: Vec<f32> = Vec::new();
+
+    while lexer.has_number()
+    {
+        numbers.push(lexer.number()?);
+    }
+
+    lexer.skip_separators();
+
+    if lexer.pos < lexer.data.len()
+    {
+        return Err(format!("invalid number list '{}'", input));
+    }
+
+    return Ok(numbers);
+}
+
+// path lexer methods
+impl<'a> PathLexer<'a>
+{
+    fn new(data: &'a str) -> PathLexer<'a>
+    {
+        return PathLexer { data: data.as_bytes(), pos: 0 };
+    }
+}
+
+impl<'a> PathLexer<'a>
+{
+    fn skip_separators(&mut self)
+    {
+        while self.pos < self.data.len() && (self.data[self.pos].is_ascii_whitespace() || self.data[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn has_number(&mut self) -> bool
+    {
+        self.skip_separators();
+
+        return self.pos < self.data.len() && matches!(self.data[self.pos], b'0'..=b'9' | b'.' | b'-' | b'+');
+    }
+
+    fn command(&mut self) -> Option<u8>
+    {
+        self.skip_separators();
+
+        if self.pos < self.data.len() && self.data[self.pos].is_ascii_alphabetic()
+        {
+            self.pos += 1;
+            return Some(self.data[self.pos - 1]);
+        }
+
+        return None;
+    }
+
+    // numbers may run into each other: "1.5.5" is 1.5 then .5 and "1-2" is 1 then -2
+    fn number(&mut self) -> Result<f32, String>
+    {
+        self.skip_separators();
+
+        let start: usize = self.pos;
+        let mut seen_dot: bool = false;
+
+        if self.pos < self.data.len() && (self.data[self.pos] == b'-' || self.data[self.pos] == b'+')
+        {
+            self.pos += 1;
+        }
+
+        while self.pos < self.data.len()
+        {
+            match self.data[self.pos]
+            {
+                b'0'..=b'9' => self.pos += 1,
+                b'.' if !seen_dot => { seen_dot = true; self.pos += 1; },
+                b'e' | b'E' if self.pos + 1 < self.data.len() && matches!(self.data[self.pos + 1], b'0'..=b'9' | b'-' | b'+') =>
+                {
+                    self.pos += 2;
+
+                    while self.pos < self.data.len() && self.data[self.pos].is_ascii_digit()
+                    {
+                        self.pos += 1;
+                    }
+
+                    break;
+                },
+                _ => break,
+            }
+        }
+
+        let text: &str = std::str::from_utf8(&self.data[start..self.pos]).unwrap_or("");
+
+        return text.parse::<f32>().map_err(|_| format!("invalid number at offset {}", start));
+    }
+
+    // arc flags are a single '0' or '1' and need no separator ("a1 1 0 00 1 1")
+    fn flag(&mut self) -> Result<bool, String>
+    {
+        self.skip_separators();
+
+        if self.pos < self.data.len() && (self.data[self.pos] == b'0' || self.data[self.pos] == b'1')
+        {
+            self.pos += 1;
+            return Ok(self.data[self.pos - 1] == b'1');
+        }
+
+        return Err(format!("invalid arc flag at offset {}", self.pos));
+    }
+}
+
+// flattens one element into strokes, working in the element's own coordinates
+struct PathBuilder<'a>
+{
+    transform: &'a Transform,
+    tolerance: f32,
+    strokes: Vec<Vec<Complex>>,
+    current: Vec<Complex>,
+}
+
+impl<'a> PathBuilder<'a>
+{
+    fn new(transform: &'a Transform) -> PathBuilder<'a>
+    {
+        let scale: f32 = transform.max_scale().max(f32::EPSILON);
+
+        return PathBuilder { transform: transform, tolerance: SVG_FLATNESS / scale, strokes: Vec::new(), current: Vec::new() };
+    }
+}
+
+impl<'a> PathBuilder<'a>
+{
+    fn move_to(&mut self, x: f32, y: f32)
+    {
+        self.finish();
+        self.line_to(x, y);
+    }
+
+    // svg's y axis points down, the renderer's imaginary axis points up
+    fn line_to(&mut self, x: f32, y: f32)
+    {
+        let (tx, ty) = self.transform.apply(x, y);
+        self.current.push(Complex::new(tx, -ty));
+    }
+
+    fn close(&mut self)
+    {
+        if self.current.len() > 1 && self.current[0] != self.current[self.current.len() - 1]
+        {
+            let start: Complex = self.current[0].clone();
+            self.current.push(start);
+        }
+
+        self.finish();
+    }
+
+    fn finish(&mut self)
+    {
+        if self.current.len() > 1
+        {
+            self.strokes.push(std::mem::take(&mut self.current));
+        }
+
+        self.current.clear();
+    }
+
+    fn cubic_to(&mut self, p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), depth: u32)
+    {
+        // distance of the control points from the chord tells how flat the curve is
+        let (dx, dy) = (p3.0 - p0.0, p3.1 - p0.1);
+        let d1: f32 = ((p1.0 - p3.0) * dy - (p1.1 - p3.1) * dx).abs();
+        let d2: f32 = ((p2.0 - p3.0) * dy - (p2.1 - p3.1) * dx).abs();
+        let chord: f32 = dx * dx + dy * dy;
+
+        let flat: bool = if chord > 0.0
+        {
+            (d1 + d2) * (d1 + d2) <= self.tolerance * self.tolerance * chord
+        }
+        else
+        {
+            // closed loop, look at the control points directly
+            let spread: f32 = ((p1.0 - p0.0).powi(2) + (p1.1 - p0.1).powi(2)).max((p2.0 - p0.0).powi(2) + (p2.1 - p0.1).powi(2));
+            spread <= self.tolerance * self.tolerance
+        };
+
+        if flat || depth >= MAX_SUBDIVISION
+        {
+            self.line_to(p3.0, p3.1);
+            return;
+        }
+
+        // de casteljau split at t = 0.5
+        let mid = |a: (f32, f32), b: (f32, f32)| -> (f32, f32) { ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5) };
+        let p01 = mid(p0, p1);
+        let p12 = mid(p1, p2);
+        let p23 = mid(p2, p3);
+        let p012 = mid(p01, p12);
+        let p123 = mid(p12, p23);
+        let center = mid(p012, p123);
+
+        self.cubic_to(p0, p01, p012, center, depth + 1);
+        self.cubic_to(center, p123, p23, p3, depth + 1);
+    }
+
+    // elliptical arc from the endpoint parametrisation (svg spec, appendix F.6.5)
+    fn arc_to(&mut self, from: (f32, f32), radii: (f32, f32), rotation: f32, large_arc: bool, sweep: bool, to: (f32, f32))
+    {
+        let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+
+        if rx == 0.0 || ry == 0.0 || from == to
+        {
+            self.line_to(to.0, to.1);
+            return;
+        }
+
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let hx: f32 = (from.0 - to.0) * 0.5;
+        let hy: f32 = (from.1 - to.1) * 0.5;
+        let x1: f32 = cos * hx + sin * hy;
+        let y1: f32 = -sin * hx + cos * hy;
+
+        // scale the radii up if they cannot reach the end point
+        let lambda: f32 = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+
+        if lambda > 1.0
+        {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator: f32 = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator: f32 = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut factor: f32 = (numerator / denominator).max(0.0).sqrt();
+
+        if large_arc == sweep
+        {
+            factor = -factor;
+        }
+
+        let cx1: f32 = factor * rx * y1 / ry;
+        let cy1: f32 = -factor * ry * x1 / rx;
+        let cx: f32 = cos * cx1 - sin * cy1 + (from.0 + to.0) * 0.5;
+        let cy: f32 = sin * cx1 + cos * cy1 + (from.1 + to.1) * 0.5;
+
+        let angle = |ux: f32, uy: f32, vx: f32, vy: f32| -> f32 { (ux * vy - uy * vx).atan2(ux * vx + uy * vy) };
+
+        let start: f32 = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut delta: f32 = angle((x1 - cx1) / rx, (y1 - cy1) / ry, (-x1 - cx1) / rx, (-y1 - cy1) / ry);
+
+        if !sweep && delta > 0.0
+        {
+            delta -= PI_2;
+        }
+        else if sweep && delta < 0.0
+        {
+            delta += PI_2;
+        }
+
+        // the angle step that keeps the chord within the tolerance of the larger radius
+        let radius: f32 = rx.max(ry);
+        let step: f32 = if self.tolerance < radius { 2.0 * (1.0 - self.tolerance / radius).acos() } else { PI_2 / 4.0 };
+        let count: usize = ((delta.abs() / step.max(0.001)).ceil() as usize).max(1);
+
+        for i in 1..count + 1
+        {
+            let theta: f32 = start + delta * i as f32 / count as f32;
+            let (ex, ey) = (rx * theta.cos(), ry * theta.sin());
+
+            if i == count
+            {
+                self.line_to(to.0, to.1);
+            }
+            else
+            {
+                self.line_to(cos * ex - sin * ey + cx, sin * ex + cos * ey + cy);
+            }
+        }
+    }
+}
+
+// flatten the d attribute of a <path>, every subpath becomes its own stroke
+pub fn parse_path_data(data: &str, transform: &Transform) -> Result<Vec<Vec<Complex>>, String>
+{
+    let mut lexer: PathLexer = PathLexer::new(data);
+    let mut builder: PathBuilder = PathBuilder::new(transform);
+
+    let mut position: (f32, f32) = (0.0, 0.0);
+    let mut subpath_start: (f32, f32) = (0.0, 0.0);
+    let mut last_control: Option<(f32, f32)> = None; // reflected by S and T
+    let mut previous: u8 = b' ';
+    let mut command: Option<u8> = None;
+
+    loop
+    {
+        let explicit: Option<u8> = lexer.command();
+
+        if explicit.is_some()
+        {
+            command = explicit;
+        }
+        else if !lexer.has_number()
+        {
+            break;
+        }
+
+        let cmd: u8 = command.ok_or(String::from("path data must start with a command"))?;
+        let relative: bool = cmd.is_ascii_lowercase();
+        let origin: (f32, f32) = if relative { position } else { (0.0, 0.0) };
+
+        // the position after z is the start of the subpath, a new stroke begins there
+        if builder.current.is_empty() && !matches!(cmd, b'M' | b'm' | b'Z' | b'z')
+        {
+            builder.move_to(position.0, position.1);
+        }
+
+        match cmd.to_ascii_uppercase()
+        {
+            b'M' =>
+            {
+                let x: f32 = lexer.number()? + origin.0;
+                let y: f32 = lexer.number()? + origin.1;
+
+                builder.move_to(x, y);
+                position = (x, y);
+                subpath_start = position;
+
+                // extra coordinate pairs after a moveto are implicit linetos
+                command = Some(if relative { b'l' } else { b'L' });
+            },
+            b'L' =>
+            {
+                position = (lexer.number()? + origin.0, lexer.number()? + origin.1);
+                builder.line_to(position.0, position.1);
+            },
+            b'H' =>
+            {
+                position.0 = lexer.number()? + origin.0;
+                builder.line_to(position.0, position.1);
+            },
+            b'V' =>
+            {
+                position.1 = lexer.number()? + origin.1;
+                builder.line_to(position.0, position.1);
+            },
+            b'C' | b'S' =>
+            {
+                let c1: (f32, f32) = if cmd.eq_ignore_ascii_case(&b'C')
+                {
+                    (lexer.number()? + origin.0, lexer.number()? + origin.1)
+                }
+                else
+                {
+                    reflect(position, last_control, matches!(previous, b'C' | b'S'))
+                };
+
+                let c2: (f32, f32) = (lexer.number()? + origin.0, lexer.number()? + origin.1);
+                let end: (f32, f32) = (lexer.number()? + origin.0, lexer.number()? + origin.1);
+
+                builder.cubic_to(position, c1, c2, end, 0);
+                last_control = Some(c2);
+                position = end;
+            },
+            b'Q' | b'T' =>
+            {
+                let control: (f32, f32) = if cmd.eq_ignore_ascii_case(&b'Q')
+                {
+                    (lexer.number()? + origin.0, lexer.number()? + origin.1)
+                }
+                else
+                {
+                    reflect(position, last_control, matches!(previous, b'Q' | b'T'))
+                };
+
+                let end: (f32, f32) = (lexer.number()? + origin.0, lexer.number()? + origin.1);
+
+                // a quadratic is a cubic with both control points 2/3 of the way to the control
+                let c1: (f32, f32) = (position.0 + (control.0 - position.0) * 2.0 / 3.0, position.1 + (control.1 - position.1) * 2.0 / 3.0);
+                let c2: (f32, f32) = (end.0 + (control.0 - end.0) * 2.0 / 3.0, end.1 + (control.1 - end.1) * 2.0 / 3.0);
+
+                builder.cubic_to(position, c1, c2, end, 0);
+                last_control = Some(control);
+                position = end;
+            },
+            b'A' =>
+            {
+                let radii: (f32, f32) = (lexer.number()?, lexer.number()?);
+                let rotation: f32 = lexer.number()?;
+                let large_arc: bool = lexer.flag()?;
+                let sweep: bool = lexer.flag()?;
+                let end: (f32, f32) = (lexer.number()? + origin.0, lexer.number()? + origin.1);
+
+                builder.arc_to(position, radii, rotation, large_arc, sweep, end);
+                position = end;
+            },
+            b'Z' =>
+            {
+                builder.close();
+                position = subpath_start;
+                command = None;
+            },
+            _ => return Err(format!("unsupported path command '{}'", cmd as char)),
+        }
+
+        previous = cmd.to_ascii_uppercase();
+    }
+
+    builder.finish();
+
+    return Ok(builder.strokes);
+}
+
+// control point of a smooth curve, the previous control point mirrored around the current point
+fn reflect(position: (f32, f32), last_control: Option<(f32, f32)>, smooth: bool) -> (f32, f32)
+{
+    match last_control
+    {
+        Some(control) if smooth => (2.0 * position.0 - control.0, 2.0 * position.1 - control.1),
+        _ => position,
+    }
+}
+
+// points attribute of <polyline> and <polygon>
+pub fn parse_points(data: &str, closed: bool, transform: &Transform) -> Result<Vec<Complex>, String>
+{
+    let numbers: Vec<f32> = parse_number_list(data)?;
+    let mut builder: PathBuilder = PathBuilder::new(transform);
+
+    for pair in numbers.chunks_exact(2)
+    {
+        builder.line_to(pair[0], pair[1]);
+    }
+
+    if closed
+    {
+        builder.close();
+    }
+
+    builder.finish();
+
+    return Ok(builder.strokes.into_iter().next().unwrap_or_default());
+}
+
+fn parse_tag(input: &str) -> Tag
+{
+    let inner: &str = input.trim_start_matches('<').trim_end_matches('>');
+    let closing: bool = inner.starts_with('/');
+    let self_closing: bool = inner.ends_with('/');
+    let inner: &str = inner.trim_matches('/');
+
+    let name_end: usize = inner.find(|c: char| c.is_whitespace()).unwrap_or(inner.len());
+    let mut tag: Tag = Tag { name: inner[..name_end].to_string(), attributes: Vec::new(), closing: closing, self_closing: self_closing };
+
+    let mut rest: &str = &inner[name_end..];
+
+    while let Some(eq) = rest.find('=')
+    {
+        let key: &str = rest[..eq].trim();
+        let value_part: &str = rest[eq + 1..].trim_start();
+        let quote: char = match value_part.chars().next() { Some(q) if q == '"' || q == '\'' => q, _ => break };
+
+        match value_part[1..].find(quote)
+        {
+            Some(end) =>
+            {
+                tag.attributes.push((key.to_string(), value_part[1..end + 1].to_string()));
+                rest = &value_part[end + 2..];
+            },
+            None => break,
+        }
+    }
+
+    return tag;
+}
+
+impl Tag
+{
+    fn attribute(&self, name: &str) -> Option<&str>
+    {
+        return self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+    }
+}
+
+// elements whose children are never drawn directly
+fn is_hidden_container(name: &str) -> bool
+{
+    return matches!(name, "defs" | "clipPath" | "mask" | "symbol" | "marker" | "pattern");
+}
+
+// extract every <path>, <polyline> and <polygon> of an svg document as separate strokes
+pub fn parse_svg(contents: &str) -> Result<Vec<Vec<Complex>>, String>
+{
+    let mut strokes: Vec<Vec<Complex>> = Vec::new();
+    let mut stack: Vec<(Transform, bool)> = Vec::from([(Transform::identity(), false)]); // (transform, hidden)
+    let mut rest: &str = contents;
+
+    while let Some(start) = rest.find('<')
+    {
+        rest = &rest[start..];
+
+        // comments, processing instructions, doctype and cdata carry no geometry
+        let skip_to: Option<&str> = if rest.starts_with("<!--") { Some("-->") }
+                                    else if rest.starts_with("<![CDATA[") { Some("]]>") }
+                                    else if rest.starts_with("<?") { Some("?>") }
+                                    else if rest.starts_with("<!") { Some(">") }
+                                    else { None };
+
+        if let Some(end_marker) = skip_to
+        {
+            let end: usize = rest.find(end_marker).ok_or(String::from("unterminated markup"))?;
+            rest = &rest[end + end_marker.len()..];
+            continue;
+        }
+
+        let end: usize = rest.find('>').ok_or(String::from("unterminated tag"))?;
+        let tag: Tag = parse_tag(&rest[..end + 1]);
+        rest = &rest[end + 1..];
+
+        if tag.closing
+        {
+            if stack.len() > 1
+            {
+                stack.pop();
+            }
+
+            continue;
+        }
+
+        let (parent, parent_hidden) = stack[stack.len() - 1].clone();
+        let transform: Transform = match tag.attribute("transform")
+        {
+            Some(value) => parent.then(&parse_transform(value)?),
+            None => parent,
+        };
+        let hidden: bool = parent_hidden || is_hidden_container(&tag.name);
+
+        if !hidden
+        {
+            match tag.name.as_str()
+            {
+                "path" => strokes.extend(parse_path_data(tag.attribute("d").unwrap_or(""), &transform)?),
+                "polyline" | "polygon" =>
+                {
+                    let points: Vec<Complex> = parse_points(tag.attribute("points").unwrap_or(""), tag.name == "polygon", &transform)?;
+
+                    if points.len() > 1
+                    {
+                        strokes.push(points);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        if !tag.self_closing
+        {
+            stack.push((transform, hidden));
+        }
+    }
+
+    return Ok(strokes);
+}
+
+pub fn load_from_svg(path: &str) -> Result<Vec<Vec<Complex>>, Error>
+{
+    let contents: String = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+
+    return parse_svg(&contents).map_err(|e| Error::format(path, &e));
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn near(a: &Complex, b: Complex) -> bool
+    {
+        return (a - &b).magnitude() < 1e-4;
+    }
+
+    #[test]
+    fn lines_and_close()
+    {
+        let strokes: Vec<Vec<Complex>> = parse_path_data("M0 0 h10 v10 H0 z", &Transform::identity()).unwrap();
+
+        assert_eq!(strokes.len(), 1);
+        assert_eq!(strokes[0].len(), 5);
+        assert!(near(&strokes[0][2], Complex::new(10.0, -10.0)));
+        assert_eq!(strokes[0][0], strokes[0][4]);
+    }
+
+    #[test]
+    fn subpaths_are_separate_strokes()
+    {
+        let strokes: Vec<Vec<Complex>> = parse_path_data("M0 0 L1 0 M5 5 l1-1", &Transform::identity()).unwrap();
+
+        assert_eq!(strokes.len(), 2);
+        assert!(near(&strokes[1][1], Complex::new(6.0, -4.0)));
+    }
+
+    #[test]
+    fn curves_stay_close_to_the_curve()
+    {
+        // a half circle of radius 10 drawn with an arc
+        let strokes: Vec<Vec<Complex>> = parse_path_data("M-10 0 A10 10 0 0 1 10 0", &Transform::identity()).unwrap();
+
+        assert!(strokes[0].len() > 4);
+
+        for point in &strokes[0]
+        {
+            assert!((point.magnitude() - 10.0).abs() < SVG_FLATNESS * 2.0);
+        }
+
+        let curve: Vec<Vec<Complex>> = parse_path_data("M0 0 C0 10 10 10 10 0 S20 -10 20 0", &Transform::identity()).unwrap();
+        assert!(curve[0].len() > 4);
+        assert!(near(&curve[0][curve[0].len() - 1], Complex::new(20.0, 0.0)));
+    }
+
+    #[test]
+    fn compact_numbers()
+    {
+        let numbers: Vec<f32> = parse_number_list("1.5.5-2e1,3").unwrap();
+
+        assert_eq!(numbers, Vec::from([1.5, 0.5, -20.0, 3.0]));
+    }
+
+    #[test]
+    fn document_with_transforms()
+    {
+        let svg: &str = r#"<?xml version="1.0"?>
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <defs><path d="M0 0 L100 100"/></defs>
+                <g transform="translate(10, 0)">
+                    <polygon points="0,0 1,0 1,1" transform="scale(2)"/>
+                </g>
+                <polyline points="0 0 5 5"/>
+            </svg>"#;
+
+        let strokes: Vec<Vec<Complex>> = parse_svg(svg).unwrap();
+
+        assert_eq!(strokes.len(), 2);
+        assert_eq!(strokes[0].len(), 4);
+        assert!(near(&strokes[0][1], Complex::new(12.0, 0.0)));
+        assert!(near(&strokes[0][2], Complex::new(12.0, -2.0)));
+    }
+}
\ No newline at end of file
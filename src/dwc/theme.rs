@@ -0,0 +1,189 @@
+extern crate sdl2;
+
+use crate::dwc::epicycle::*;
+
+use sdl2::pixels::Color;
+
+// colormaps, from low to high values
+pub const HEAT: [Color; 5] = [Color::RGB(0, 0, 255), Color::RGB(0, 255, 255), Color::RGB(0, 255, 0), Color::RGB(255, 255, 0), Color::RGB(255, 0, 0)];
+pub const VIRIDIS: [Color; 5] = [Color::RGB(68, 1, 84), Color::RGB(59, 82, 139), Color::RGB(33, 145, 140), Color::RGB(94, 201, 98), Color::RGB(253, 231, 37)];
+pub const CIVIDIS: [Color; 5] = [Color::RGB(0, 32, 77), Color::RGB(65, 77, 108), Color::RGB(124, 123, 120), Color::RGB(187, 175, 113), Color::RGB(255, 233, 69)];
+pub const GRAYS: [Color; 2] = [Color::RGB(170, 170, 170), Color::RGB(0, 0, 0)];
+
+// how the circles of the chain are coloured
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum CircleColoring
+{
+    Uniform, // the circle colour of the theme
+    Magnitude, // small circles at the low end of the colormap
+    Frequency, // slow circles at the low end of the colormap
+}
+
+// the colours of everything drawn
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Theme
+{
+    pub name: &'static str,
+    pub background: Color,
+    pub circle: Color, // its alpha is used for colormapped circles too
+    pub arm: Color,
+    pub trail: Color,
+    pub original: Color, // the path under the convergence demo
+    pub sketch: Color,
+    pub hud: Color,
+    pub timeline: Color,
+    pub colormap: &'static [Color],
+    pub additive: bool, // colours add up where they overlap, which only works on dark backgrounds
+}
+
+pub const THEMES: [Theme; 5] =
+[
+    Theme { name: "dark",
+            background: Color::RGB(5, 20, 60),
+            circle: Color::RGBA(255, 255, 255, 70),
+            arm: Color::RGB(255, 255, 255),
+            trail: Color::RGB(255, 100, 0),
+            original: Color::RGB(50, 50, 70),
+            sketch: Color::RGB(120, 120, 120),
+            hud: Color::RGB(200, 200, 200),
+            timeline: Color::RGB(255, 255, 255),
+            colormap: &HEAT,
+            additive: true },
+    Theme { name: "light",
+            background: Color::RGB(245, 245, 240),
+            circle: Color::RGBA(40, 40, 60, 90),
+            arm: Color::RGB(40, 40, 60),
+            trail: Color::RGB(200, 60, 0),
+            original: Color::RGB(190, 190, 200),
+            sketch: Color::RGB(150, 150, 150),
+            hud: Color::RGB(30, 30, 30),
+            timeline: Color::RGB(40, 40, 60),
+            colormap: &VIRIDIS,
+            additive: false },
+    Theme { name: "print",
+            background: Color::RGB(255, 255, 255),
+            circle: Color::RGBA(0, 0, 0, 60),
+            arm: Color::RGB(0, 0, 0),
+            trail: Color::RGB(0, 0, 0),
+            original: Color::RGB(200, 200, 200),
+            sketch: Color::RGB(160, 160, 160),
+            hud: Color::RGB(0, 0, 0),
+            timeline: Color::RGB(0, 0, 0),
+            colormap: &GRAYS,
+            additive: false },
+    Theme { name: "high-contrast",
+            background: Color::RGB(0, 0, 0),
+            circle: Color::RGBA(255, 255, 255, 160),
+            arm: Color::RGB(255, 255, 255),
+            trail: Color::RGB(255, 255, 0),
+            original: Color::RGB(0, 120, 255),
+            sketch: Color::RGB(255, 255, 255),
+            hud: Color::RGB(255, 255, 255),
+            timeline: Color::RGB(255, 255, 255),
+            colormap: &HEAT,
+            additive: true },
+    // okabe-ito colours, told apart with every common colour vision deficiency
+    Theme { name: "colour-blind",
+            background: Color::RGB(20, 20, 30),
+            circle: Color::RGBA(86, 180, 233, 90),
+            arm: Color::RGB(240, 228, 66),
+            trail: Color::RGB(230, 159, 0),
+            original: Color::RGB(0, 80, 130),
+            sketch: Color::RGB(150, 150, 150),
+            hud: Color::RGB(220, 220, 220),
+            timeline: Color::RGB(86, 180, 233),
+            colormap: &CIVIDIS,
+            additive: true },
+];
+
+pub fn theme_index(name: &str) -> Option<usize>
+{
+    return THEMES.iter().position(|theme| theme.name == name);
+}
+
+// linear interpolation between the stops, value from 0 to 1
+pub fn sample_colormap(stops: &[Color], value: f32) -> Color
+{
+    if stops.len() < 2
+    {
+        return stops.first().cloned().unwrap_or(Color::RGB(255, 255, 255));
+    }
+
+    let position: f32 = value.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let i: usize = (position as usize).min(stops.len() - 2);
+    let s: f32 = position - i as f32;
+
+    let mix = |a: u8, b: u8| -> u8 { (a as f32 + (b as f32 - a as f32) * s).round() as u8 };
+    let (a, b) = (stops[i], stops[i + 1]);
+
+    return Color::RGBA(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a));
+}
+
+// colour of every circle of the chain
+pub fn circle_colors(epicycles: &[Epicycle], coloring: CircleColoring, theme: &Theme) -> Vec<Color>
+{
+    let values: Vec<f32> = match coloring
+    {
+        CircleColoring::Uniform => return vec![theme.circle; epicycles.len()],
+        CircleColoring::Magnitude =>
+        {
+            // radii fall off quickly, so the map spans three decades below the largest
+            let max: f32 = epicycles.iter().map(|e| e.c0.magnitude()).fold(0.0, f32::max);
+            epicycles.iter().map(|e| if max > 0.0 { 1.0 + (e.c0.magnitude() / max).log10() / 3.0 } else { 0.0 }).collect()
+        },
+        CircleColoring::Frequency =>
+        {
+            let max: f32 = epicycles.iter().map(|e| e.f.abs()).fold(0.0, f32::max);
+            epicycles.iter().map(|e| if max > 0.0 { e.f.abs() / max } else { 0.0 }).collect()
+        },
+    };
+
+    return values.iter().map(|value| { let c: Color = sample_colormap(theme.colormap, *value); Color::RGBA(c.r, c.g, c.b, theme.circle.a) }).collect();
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    use crate::dwc::complex::*;
+
+    #[test]
+    fn colormap()
+    {
+        assert_eq!(sample_colormap(&GRAYS, 0.0), Color::RGB(170, 170, 170));
+        assert_eq!(sample_colormap(&GRAYS, 1.0), Color::RGB(0, 0, 0));
+        assert_eq!(sample_colormap(&GRAYS, 0.5), Color::RGB(85, 85, 85));
+        assert_eq!(sample_colormap(&HEAT, 2.0), Color::RGB(255, 0, 0));
+        assert_eq!(sample_colormap(&HEAT, 0.25), Color::RGB(0, 255, 255));
+    }
+
+    #[test]
+    fn circles()
+    {
+        let epicycles: Vec<Epicycle> = vec![Epicycle::new(Complex::new(10.0, 0.0), 1.0), Epicycle::new(Complex::new(0.01, 0.0), -4.0)];
+        let theme: &Theme = &THEMES[0];
+
+        assert_eq!(circle_colors(&epicycles, CircleColoring::Uniform, theme), vec![theme.circle; 2]);
+
+        let by_size: Vec<Color> = circle_colors(&epicycles, CircleColoring::Magnitude, theme);
+        assert_eq!(by_size[0], Color::RGBA(255, 0, 0, theme.circle.a));
+        assert_eq!(by_size[1], Color::RGBA(0, 0, 255, theme.circle.a));
+
+        let by_frequency: Vec<Color> = circle_colors(&epicycles, CircleColoring::Frequency, theme);
+        assert_eq!(by_frequency[1], Color::RGBA(255, 0, 0, theme.circle.a));
+    }
+
+    #[test]
+    fn names()
+    {
+        assert_eq!(theme_index("dark"), Some(0));
+        assert_eq!(theme_index("colour-blind"), Some(4));
+        assert_eq!(theme_index("neon"), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use crate::dwc::complex::*;
+use crate::dwc::image_loader::*;
+use crate::dwc::edges::*;
+use crate::dwc::error::*;
+
+pub const SINGLE_LINE_MAX_POINTS: usize = 6000; // edge pixels kept before ordering them
+const NEIGHBOUR_COUNT: usize = 8; // candidates looked at by 2-opt for every point
+const MAX_PASSES: usize = 50;
+
+fn distance(a: &Complex, b: &Complex) -> f32
+{
+    return (a - b).magnitude();
+}
+
+// keep at most `max_count` points, at most one per cell of a grid that grows until they fit
+pub fn thin_points(points: &[Complex], max_count: usize) -> Vec<Complex>
+{
+    let mut cell_size: f32 = 1.0;
+    let mut kept: Vec<Complex> = points.to_vec();
+
+    while kept.len() > max_count
+    {
+        cell_size *= 1.25;
+
+        let mut occupied: HashSet<(i64, i64)> = HashSet::new();
+        kept = points.iter()
+                     .filter(|p| occupied.insert(((p.real / cell_size).floor() as i64, (p.img / cell_size).floor() as i64)))
+                     .cloned()
+                     .collect();
+    }
+
+    return kept;
+}
+
+// buckets points in a uniform grid to find close neighbours without testing every pair
+struct SpatialGrid
+{
+    cell_size: f32,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    len: usize, // points still in the grid
+}
+
+impl SpatialGrid
+{
+    fn new(points: &[Complex]) -> SpatialGrid
+    {
+        let mut min: Complex = Complex::new(f32::MAX, f32::MAX);
+        let mut max: Complex = Complex::new(f32::MIN, f32::MIN);
+
+        for p in points
+        {
+            min = Complex::new(min.real.min(p.real), min.img.min(p.img));
+            max = Complex::new(max.real.max(p.real), max.img.max(p.img));
+        }
+
+        // around two points per cell on average
+        let area: f32 = ((max.real - min.real) * (max.img - min.img)).max(1.0);
+        let cell_size: f32 = (2.0 * area / points.len().max(1) as f32).sqrt().max(0.001);
+        let mut grid: SpatialGrid = SpatialGrid { cell_size: cell_size, cells: HashMap::new(), len: points.len() };
+
+        for (i, p) in points.iter().enumerate()
+        {
+            grid.cells.entry(grid.cell(p)).or_default().push(i);
+        }
+
+        return grid;
+    }
+}
+
+impl SpatialGrid
+{
+    fn cell(&self, p: &Complex) -> (i64, i64)
+    {
+        return ((p.real / self.cell_size).floor() as i64, (p.img / self.cell_size).floor() as i64);
+    }
+
+    fn remove(&mut self, p: &Complex, index: usize)
+    {
+        let cell: (i64, i64) = self.cell(p);
+
+        if let Some(bucket) = self.cells.get_mut(&cell)
+        {
+            let before: usize = bucket.len();
+            bucket.retain(|i| *i != index);
+            self.len -= before - bucket.len();
+
+            if bucket.is_empty()
+            {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    // the `count` closest points still in the grid, closest first
+    fn nearest(&self, points: &[Complex], p: &Complex, count: usize) -> Vec<usize>
+    {
+        let center: (i64, i64) = self.cell(p);
+        let wanted: usize = count.min(self.len);
+        let mut found: Vec<(f32, usize)> = Vec::new();
+        let mut ring: i64 = 0;
+
+        if wanted == 0
+        {
+            return Vec::new();
+        }
+
+        // rings of cells further than the k-th closest point found cannot hold anything closer
+        loop
+        {
+            for cy in center.1 - ring..center.1 + ring + 1
+            {
+                for cx in center.0 - ring..center.0 + ring + 1
+                {
+                    if (cx - center.0).abs() != ring && (cy - center.1).abs() != ring
+                    {
+                        continue;
+                    }
+
+                    if let Some(bucket) = self.cells.get(&(cx, cy))
+                    {
+                        found.extend(bucket.iter().map(|i| (distance(p, &points[*i]), *i)));
+                    }
+                }
+            }
+
+            found.sort_by(|a, b| a.0.total_cmp(&b.0));
+            found.truncate(count);
+
+            // either every remaining point has been found or nothing unseen can be closer
+            if found.len() == wanted && (wanted < count || found[wanted - 1].0 <= ring as f32 * self.cell_size)
+            {
+                break;
+            }
+
+            ring += 1;
+        }
+
+        return found.into_iter().map(|(_, i)| i).collect();
+    }
+}
+
+// greedy tour, always walking to the closest point not yet visited
+pub fn nearest_neighbour_tour(points: &[Complex]) -> Vec<usize>
+{
+    let mut tour: Vec<usize> = Vec::new();
+
+    if points.is_empty()
+    {
+        return tour;
+    }
+
+    let mut grid: SpatialGrid = SpatialGrid::new(points);
+    let mut current: usize = 0;
+
+    grid.remove(&points[0], 0);
+    tour.push(0);
+
+    while tour.len() < points.len()
+    {
+        current = grid.nearest(points, &points[current], 1)[0];
+        grid.remove(&points[current], current);
+        tour.push(current);
+    }
+
+    return tour;
+}
+
+// improve a closed tour with 2-opt moves, only trying to connect each point to its close neighbours
+pub fn two_opt(points: &[Complex], tour: &mut [usize])
+{
+    let n: usize = tour.len();
+
+    if n < 4
+    {
+        return;
+    }
+
+    let grid: SpatialGrid = SpatialGrid::new(points);
+    let neighbours: Vec<Vec<usize>> = (0..points.len()).map(|i| grid.nearest(points, &points[i], NEIGHBOUR_COUNT + 1)).collect();
+
+    // position of every point in the tour
+    let mut position: Vec<usize> = vec![0; points.len()];
+
+    for (i, p) in tour.iter().enumerate()
+    {
+        position[*p] = i;
+    }
+
+    for _ in 0..MAX_PASSES
+    {
+        let mut improved: bool = false;
+
+        for i in 0..n
+        {
+            let a: usize = tour[i];
+            let b: usize = tour[(i + 1) % n];
+
+            for c in neighbours[a].iter().cloned()
+            {
+                let j: usize = position[c];
+                let d: usize = tour[(j + 1) % n];
+
+                if c == a || c == b || d == a
+                {
+                    continue;
+                }
+
+                // replace edges a-b and c-d with a-c and b-d
+                let gain: f32 = distance(&points[a], &points[b]) + distance(&points[c], &points[d])
+                              - distance(&points[a], &points[c]) - distance(&points[b], &points[d]);
+
+                if gain > 1e-4
+                {
+                    // reverse the part of the tour between b and c
+                    let (mut from, mut to) = ((i + 1) % n, j);
+                    let length: usize = (to + n - from) % n + 1;
+
+                    for _ in 0..length / 2
+                    {
+                        tour.swap(from, to);
+                        position[tour[from]] = from;
+                        position[tour[to]] = to;
+                        from = (from + 1) % n;
+                        to = (to + n - 1) % n;
+                    }
+
+                    improved = true;
+                    break;
+                }
+            }
+        }
+
+        if !improved
+        {
+            break;
+        }
+    }
+}
+
+// order the points into one closed continuous line, as short as reasonably possible
+pub fn order_points(points: &[Complex]) -> Vec<Complex>
+{
+    let mut tour: Vec<usize> = nearest_neighbour_tour(points);
+    two_opt(points, &mut tour);
+
+    return tour.into_iter().map(|i| points[i].clone()).collect();
+}
+
+// detect the edges of an image and join them into one continuous line (y pointing up)
+pub fn load_single_line(path: &str) -> Result<Vec<Complex>, Error>
+{
+    let image: GrayImage = load_image(path)?;
+    let edges: Vec<Complex> = canny(&image, CANNY_LOW, CANNY_HIGH).into_iter()
+                                                                    .map(|(x, y)| Complex::new(x as f32, -(y as f32)))
+                                                                    .collect();
+
+    if edges.is_empty()
+    {
+        return Err(Error::EmptyPath(String::from(path)));
+    }
+
+    return Ok(order_points(&thin_points(&edges, SINGLE_LINE_MAX_POINTS)));
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use crate::dwc::path::*;
+
+    #[test]
+    fn visits_every_point_once()
+    {
+        let points: Vec<Complex> = (0..100).map(|i| Complex::new((i * 37 % 100) as f32, (i * 61 % 100) as f32)).collect();
+        let mut tour: Vec<usize> = order_points(&points).iter().map(|p| points.iter().position(|q| q == p).unwrap()).collect();
+
+        tour.sort();
+        tour.dedup();
+        assert_eq!(tour.len(), 100);
+    }
+
+    #[test]
+    fn untangles_a_circle()
+    {
+        // points on a circle, shuffled: the best tour walks around the circle
+        let points: Vec<Complex> = (0..64).map(|i| euler_formula((i * 23 % 64) as f32 / 64.0 * 6.2831855) * 10.0).collect();
+        let ordered: Vec<Complex> = order_points(&points);
+
+        assert!(closed_length(&ordered) < 63.0 * 1.05);
+    }
+
+    #[test]
+    fn thinning()
+    {
+        let points: Vec<Complex> = (0..1000).map(|i| Complex::new((i % 40) as f32, (i / 40) as f32)).collect();
+
+        assert!(thin_points(&points, 300).len() <= 300);
+    }
+}
\ No newline at end of file
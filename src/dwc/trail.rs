@@ -0,0 +1,102 @@
+use crate::dwc::complex::*;
+
+// the last `max_length` points drawn, stored in a ring buffer
+pub struct Trail
+{
+    max_length: usize,
+    values: Vec<Complex>,
+    times: Vec<f32>, // the time each value was drawn at
+    head: usize // index of the oldest value once the buffer is full
+}
+
+// trail methods
+impl Trail
+{
+    pub fn new(max_length: usize) -> Trail
+    {
+        return Trail{ max_length: max_length, values: Vec::with_capacity(max_length), times: Vec::with_capacity(max_length), head: 0 };
+    }
+}
+
+impl Trail
+{
+    pub fn push(&mut self, value: Complex, t: f32)
+    {
+        if self.max_length == 0
+        {
+            return;
+        }
+
+        if self.values.len() < self.max_length
+        {
+            self.values.push(value);
+            self.times.push(t);
+        }
+        else
+        {
+            // overwrite the oldest value instead of shifting everything
+            self.values[self.head] = value;
+            self.times[self.head] = t;
+            self.head = (self.head + 1) % self.max_length;
+        }
+    }
+
+    // idx 0 is the oldest value
+    pub fn get_value(&self, idx: usize) -> &Complex
+    {
+        return &self.values[(self.head + idx) % self.values.len()];
+    }
+
+    pub fn get_time(&self, idx: usize) -> f32
+    {
+        return self.times[(self.head + idx) % self.times.len()];
+    }
+
+    pub fn get_length(&self) -> usize
+    {
+        return self.values.len();
+    }
+
+    pub fn get_max_length(&self) -> usize
+    {
+        return self.max_length;
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.values.clear();
+        self.times.clear();
+        self.head = 0;
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_values()
+    {
+        let mut trail: Trail = Trail::new(3);
+
+        for i in 0..5
+        {
+            trail.push(Complex::new(i as f32, 0.0), i as f32);
+        }
+
+        assert_eq!(trail.get_length(), 3);
+        assert_eq!(trail.get_value(0).real, 2.0);
+        assert_eq!(trail.get_value(2).real, 4.0);
+        assert_eq!(trail.get_time(1), 3.0);
+    }
+
+    #[test]
+    fn empty_trail()
+    {
+        let mut trail: Trail = Trail::new(0);
+        trail.push(Complex::new(1.0, 0.0), 0.0);
+
+        assert_eq!(trail.get_length(), 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,234 @@
+extern crate sdl2;
+
+use crate::dwc::complex::*;
+use crate::dwc::path::*;
+
+use sdl2::pixels::Color;
+
+const ERROR_RANGE: f32 = 0.05; // errors of this fraction of the shape's size are red
+
+// what decides the colour of each trail segment
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum TrailColoring
+{
+    Solid, // the trail colour everywhere
+    Hue, // rainbow from the oldest to the newest point
+    Speed, // slow is blue, fast is red
+    Curvature, // straight is blue, sharp turns are red
+    Error, // distance to the original path at the same time, exact is blue and ERROR_RANGE red
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub struct TrailStyle
+{
+    pub coloring: TrailColoring,
+    pub fade: bool // older segments become transparent
+}
+
+impl TrailStyle
+{
+    pub fn new(coloring: TrailColoring, fade: bool) -> TrailStyle
+    {
+        return TrailStyle { coloring: coloring, fade: fade };
+    }
+}
+
+// hue in degrees, saturation and value from 0 to 1
+pub fn hsv_to_color(hue: f32, saturation: f32, value: f32) -> Color
+{
+    let h: f32 = hue.rem_euclid(360.0) / 60.0;
+    let c: f32 = value * saturation;
+    let x: f32 = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m: f32 = value - c;
+
+    let (r, g, b) = match h as u32
+    {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let to_byte = |v: f32| -> u8 { ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8 };
+
+    return Color::RGB(to_byte(r), to_byte(g), to_byte(b));
+}
+
+// blue (0) through green to red (1)
+pub fn heat_color(value: f32) -> Color
+{
+    return hsv_to_color(240.0 * (1.0 - value.clamp(0.0, 1.0)), 1.0, 1.0);
+}
+
+// scale the values so the largest one is 1
+fn normalize(values: &mut [f32])
+{
+    let max: f32 = values.iter().cloned().fold(0.0, f32::max);
+
+    if max > 0.0
+    {
+        for value in values.iter_mut()
+        {
+            *value /= max;
+        }
+    }
+}
+
+// colour of every segment of the trail (one less than the number of points), oldest first
+// `reference` is the original path, needed to colour by error
+pub fn trail_colors(points: &[Complex], times: &[f32], style: &TrailStyle, base: Color, reference: Option<&[Complex]>) -> Vec<Color>
+{
+    let count: usize = points.len().saturating_sub(1);
+    let mut values: Vec<f32> = vec![0.0; count];
+
+    match style.coloring
+    {
+        TrailColoring::Solid => {},
+        TrailColoring::Hue =>
+        {
+            for (i, value) in values.iter_mut().enumerate()
+            {
+                *value = i as f32 / count.max(1) as f32;
+            }
+        },
+        TrailColoring::Speed =>
+        {
+            for i in 0..count
+            {
+                let dt: f32 = (times[i + 1] - times[i]).abs();
+                values[i] = if dt > 0.0 { (&points[i + 1] - &points[i]).magnitude() / dt } else { 0.0 };
+            }
+
+            normalize(&mut values);
+        },
+        TrailColoring::Curvature =>
+        {
+            // turning angle at the start of each segment, per unit of length
+            for i in 1..count
+            {
+                let a: Complex = &points[i] - &points[i - 1];
+                let b: Complex = &points[i + 1] - &points[i];
+                let length: f32 = a.magnitude() + b.magnitude();
+
+                let turn: f32 = (a.real * b.img - a.img * b.real).atan2(a.real * b.real + a.img * b.img).abs();
+                values[i] = if length > 0.0 { turn / length } else { 0.0 };
+            }
+
+            normalize(&mut values);
+        },
+        TrailColoring::Error =>
+        {
+            if let Some(path) = reference.filter(|path| !path.is_empty())
+            {
+                // the series has no constant term, so the trail is centred on the origin and the path on its mean
+                let center: Complex = centroid(path);
+                let size: f32 = path.iter().map(|p| (p - &center).magnitude()).fold(0.0, f32::max);
+
+                for i in 0..count
+                {
+                    let index: usize = (times[i + 1].rem_euclid(1.0) * path.len() as f32).round() as usize % path.len();
+                    let error: f32 = (&points[i + 1] + &center - &path[index]).magnitude();
+
+                    values[i] = if size > 0.0 { (error / (ERROR_RANGE * size)).min(1.0) } else { 0.0 };
+                }
+            }
+        },
+    }
+
+    let mut colors: Vec<Color> = Vec::with_capacity(count);
+
+    for (i, value) in values.iter().enumerate()
+    {
+        let mut color: Color = match style.coloring
+        {
+            TrailColoring::Solid => base,
+            TrailColoring::Hue => hsv_to_color(300.0 * value, 1.0, 1.0),
+            _ => heat_color(*value),
+        };
+
+        color.a = if style.fade
+        {
+            (base.a as f32 * (i + 1) as f32 / count as f32) as u8
+        }
+        else
+        {
+            base.a
+        };
+
+        colors.push(color);
+    }
+
+    return colors;
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    use crate::dwc::common::*;
+    use crate::dwc::epicycle::*;
+
+    #[test]
+    fn primary_hues()
+    {
+        assert_eq!(hsv_to_color(0.0, 1.0, 1.0), Color::RGB(255, 0, 0));
+        assert_eq!(hsv_to_color(120.0, 1.0, 1.0), Color::RGB(0, 255, 0));
+        assert_eq!(hsv_to_color(240.0, 1.0, 1.0), Color::RGB(0, 0, 255));
+        assert_eq!(hsv_to_color(0.0, 0.0, 1.0), Color::RGB(255, 255, 255));
+    }
+
+    #[test]
+    fn fade_by_age()
+    {
+        let points: Vec<Complex> = (0..5).map(|i| Complex::new(i as f32, 0.0)).collect();
+        let times: Vec<f32> = (0..5).map(|i| i as f32 * 0.1).collect();
+
+        let colors: Vec<Color> = trail_colors(&points, &times, &TrailStyle::new(TrailColoring::Solid, true), Color::RGB(255, 100, 0), None);
+
+        assert_eq!(colors.len(), 4);
+        assert!(colors[0].a < colors[3].a);
+        assert_eq!(colors[3].a, 255);
+    }
+
+    #[test]
+    fn speed_coloring()
+    {
+        // the last segment moves twice as fast
+        let points: Vec<Complex> = Vec::from([Complex::new(0.0, 0.0), Complex::new(1.0, 0.0), Complex::new(3.0, 0.0)]);
+        let times: Vec<f32> = Vec::from([0.0, 1.0, 2.0]);
+
+        let colors: Vec<Color> = trail_colors(&points, &times, &TrailStyle::new(TrailColoring::Speed, false), Color::RGB(0, 0, 0), None);
+
+        assert_eq!(colors[1], heat_color(1.0));
+        assert_eq!(colors[0], heat_color(0.5));
+    }
+
+    #[test]
+    fn error_coloring()
+    {
+        // an ellipse away from the origin, drawn by every term of its series
+        let path: Vec<Complex> = (0..16).map(|i| { let a: f32 = i as f32 / 16.0 * PI_2; Complex::new(100.0 + 20.0 * a.cos(), 50.0 + 10.0 * a.sin()) }).collect();
+        let epicycles: Vec<Epicycle> = compute_epicycles(&path, 8).unwrap();
+
+        let times: Vec<f32> = (0..16).map(|i| i as f32 / 16.0).collect();
+        let points: Vec<Complex> = times.iter().map(|t| Epicycle::get_combined_position(&epicycles, *t)).collect();
+        let style: TrailStyle = TrailStyle::new(TrailColoring::Error, false);
+
+        let colors: Vec<Color> = trail_colors(&points, &times, &style, Color::RGB(0, 0, 0), Some(&path));
+        assert!(colors.iter().all(|c| *c == heat_color(0.0)));
+
+        // half a turn late, as far from the path as it gets
+        let late: Vec<f32> = times.iter().map(|t| t + 0.5).collect();
+        let colors: Vec<Color> = trail_colors(&points, &late, &style, Color::RGB(0, 0, 0), Some(&path));
+        assert!(colors.iter().all(|c| *c == heat_color(1.0)));
+    }
+}
\ No newline at end of file
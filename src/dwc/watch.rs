@@ -0,0 +1,76 @@
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+// notices when a file is modified, by polling its modification time
+pub struct FileWatch
+{
+    pub path: String,
+    modified: Option<SystemTime>,
+    interval: Duration, // time between two checks
+    last_check: Instant,
+}
+
+// file watch methods
+impl FileWatch
+{
+    pub fn new(path: &str, interval: Duration) -> FileWatch
+    {
+        return FileWatch { path: String::from(path), modified: modified_time(path), interval: interval, last_check: Instant::now() };
+    }
+}
+
+impl FileWatch
+{
+    // true once for every change, a file that disappears (editors often save through a new file) is not one
+    pub fn poll(&mut self) -> bool
+    {
+        if self.last_check.elapsed() < self.interval
+        {
+            return false;
+        }
+
+        self.last_check = Instant::now();
+
+        let modified: Option<SystemTime> = modified_time(&self.path);
+
+        if modified.is_none() || modified == self.modified
+        {
+            return false;
+        }
+
+        self.modified = modified;
+
+        return true;
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime>
+{
+    return std::fs::metadata(path).and_then(|m| m.modified()).ok();
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn modification()
+    {
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("dwc_watch_{}.txt", std::process::id()));
+        let file: String = path.to_string_lossy().to_string();
+        std::fs::write(&path, "0 0\n1 1\n").unwrap();
+
+        let mut watch: FileWatch = FileWatch::new(&file, Duration::ZERO);
+        assert!(!watch.poll());
+
+        let later: SystemTime = SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(watch.poll());
+        assert!(!watch.poll());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watch.poll());
+    }
+}
\ No newline at end of file